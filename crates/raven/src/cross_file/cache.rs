@@ -5,7 +5,7 @@
 //
 
 use std::num::NonZeroUsize;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 
 use lru::LruCache;
 use tower_lsp::lsp_types::Url;
@@ -20,18 +20,136 @@ pub(crate) fn non_zero_or(value: usize, default: usize) -> NonZeroUsize {
 /// Default capacity for the metadata cache
 const DEFAULT_METADATA_CACHE_CAPACITY: usize = 1000;
 
+/// On-disk persistence for [`CrossFileMetadata`], keyed by document URI.
+///
+/// Implementations back [`MetadataCache`] so the index survives a server
+/// restart without forcing a full re-parse of every file in the workspace.
+/// `load`/`store`/`remove` are expected to be cheap enough to call on every
+/// cache miss and every write; an implementation that needs to batch or
+/// debounce disk I/O should do so internally.
+pub trait MetadataStore: Send + Sync {
+    fn load(&self, uri: &Url) -> Option<CrossFileMetadata>;
+    fn store(&self, uri: &Url, meta: &CrossFileMetadata);
+    fn remove(&self, uri: &Url);
+}
+
+/// Bumped whenever the on-disk encoding of [`CrossFileMetadata`] changes
+/// incompatibly. Entries written under an older version are treated as a
+/// miss (and transparently overwritten) rather than failing to deserialize.
+pub const METADATA_SCHEMA_VERSION: u32 = 1;
+
+/// A [`CrossFileMetadata`] value as actually written to a [`MetadataStore`],
+/// tagged with the schema version it was written under.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct StoredMetadata {
+    schema_version: u32,
+    meta: CrossFileMetadata,
+}
+
+/// `redb`-backed [`MetadataStore`].
+///
+/// Each entry is the URI string mapped to a JSON-encoded [`StoredMetadata`].
+/// JSON (rather than a binary format) keeps the on-disk representation
+/// readable for debugging and tolerant of the schema evolving field-by-field;
+/// the version tag, not the wire format, is what guards against staleness.
+pub struct RedbMetadataStore {
+    db: redb::Database,
+}
+
+const METADATA_TABLE: redb::TableDefinition<&str, &[u8]> =
+    redb::TableDefinition::new("cross_file_metadata");
+
+impl RedbMetadataStore {
+    /// Open (creating if necessary) a redb database at `path` for use as a
+    /// metadata store.
+    pub fn open(path: &std::path::Path) -> anyhow::Result<Self> {
+        let db = redb::Database::create(path)?;
+        // Ensure the table exists so later reads don't need to special-case
+        // "table not yet created" as distinct from "key not found".
+        let txn = db.begin_write()?;
+        txn.open_table(METADATA_TABLE)?;
+        txn.commit()?;
+        Ok(Self { db })
+    }
+}
+
+impl MetadataStore for RedbMetadataStore {
+    fn load(&self, uri: &Url) -> Option<CrossFileMetadata> {
+        let txn = self.db.begin_read().ok()?;
+        let table = txn.open_table(METADATA_TABLE).ok()?;
+        let bytes = table.get(uri.as_str()).ok()??.value().to_vec();
+        let stored: StoredMetadata = serde_json::from_slice(&bytes).ok()?;
+        if stored.schema_version != METADATA_SCHEMA_VERSION {
+            log::trace!(
+                "Discarding {} metadata entry written under schema v{}, current is v{}",
+                uri,
+                stored.schema_version,
+                METADATA_SCHEMA_VERSION
+            );
+            return None;
+        }
+        Some(stored.meta)
+    }
+
+    fn store(&self, uri: &Url, meta: &CrossFileMetadata) {
+        let stored = StoredMetadata {
+            schema_version: METADATA_SCHEMA_VERSION,
+            meta: meta.clone(),
+        };
+        let Ok(bytes) = serde_json::to_vec(&stored) else {
+            return;
+        };
+        let result: anyhow::Result<()> = (|| {
+            let txn = self.db.begin_write()?;
+            {
+                let mut table = txn.open_table(METADATA_TABLE)?;
+                table.insert(uri.as_str(), bytes.as_slice())?;
+            }
+            txn.commit()?;
+            Ok(())
+        })();
+        if let Err(err) = result {
+            log::warn!("Failed to persist metadata for {}: {}", uri, err);
+        }
+    }
+
+    fn remove(&self, uri: &Url) {
+        let result: anyhow::Result<()> = (|| {
+            let txn = self.db.begin_write()?;
+            {
+                let mut table = txn.open_table(METADATA_TABLE)?;
+                table.remove(uri.as_str())?;
+            }
+            txn.commit()?;
+            Ok(())
+        })();
+        if let Err(err) = result {
+            log::warn!("Failed to remove persisted metadata for {}: {}", uri, err);
+        }
+    }
+}
+
 /// Metadata cache with LRU eviction and interior mutability.
 ///
 /// Uses `peek()` for reads (no LRU promotion, works under read lock) and
 /// `push()` for writes (promotes/evicts under write lock). This makes eviction
 /// "LRU by insertion/update time" which keeps the read path fully concurrent.
+///
+/// When constructed with a [`MetadataStore`] (see [`MetadataCache::with_store`]),
+/// the LRU becomes a write-through cache in front of it: a miss falls back to
+/// the store and repopulates the LRU, and every `insert`/`remove`/
+/// `invalidate_many` propagates to the store. Without one, it behaves exactly
+/// as the purely in-memory cache it always was.
 pub struct MetadataCache {
     inner: RwLock<LruCache<Url, CrossFileMetadata>>,
+    store: Option<Arc<dyn MetadataStore>>,
 }
 
 impl std::fmt::Debug for MetadataCache {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("MetadataCache").finish_non_exhaustive()
+        f.debug_struct("MetadataCache")
+            .field("persistent", &self.store.is_some())
+            .finish_non_exhaustive()
     }
 }
 
@@ -50,20 +168,46 @@ impl MetadataCache {
         let cap = non_zero_or(cap, DEFAULT_METADATA_CACHE_CAPACITY);
         Self {
             inner: RwLock::new(LruCache::new(cap)),
+            store: None,
+        }
+    }
+
+    /// A write-through cache in front of `store`, so entries survive a
+    /// restart: a miss falls back to `store.load` and repopulates the LRU,
+    /// and writes/removals propagate to `store` as they happen.
+    pub fn with_store(cap: usize, store: Arc<dyn MetadataStore>) -> Self {
+        let cap = non_zero_or(cap, DEFAULT_METADATA_CACHE_CAPACITY);
+        Self {
+            inner: RwLock::new(LruCache::new(cap)),
+            store: Some(store),
         }
     }
 
     pub fn get(&self, uri: &Url) -> Option<CrossFileMetadata> {
-        self.inner.read().ok()?.peek(uri).cloned()
+        if let Some(meta) = self.inner.read().ok()?.peek(uri).cloned() {
+            return Some(meta);
+        }
+
+        let meta = self.store.as_ref()?.load(uri)?;
+        if let Ok(mut guard) = self.inner.write() {
+            guard.push(uri.clone(), meta.clone());
+        }
+        Some(meta)
     }
 
     pub fn insert(&self, uri: Url, meta: CrossFileMetadata) {
+        if let Some(store) = &self.store {
+            store.store(&uri, &meta);
+        }
         if let Ok(mut guard) = self.inner.write() {
             guard.push(uri, meta);
         }
     }
 
     pub fn remove(&self, uri: &Url) {
+        if let Some(store) = &self.store {
+            store.remove(uri);
+        }
         if let Ok(mut guard) = self.inner.write() {
             guard.pop(uri);
         }
@@ -85,6 +229,9 @@ impl MetadataCache {
         if let Ok(mut guard) = self.inner.write() {
             let mut count = 0;
             for uri in uris {
+                if let Some(store) = &self.store {
+                    store.remove(uri);
+                }
                 if guard.pop(uri).is_some() {
                     count += 1;
                 }
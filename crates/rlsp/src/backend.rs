@@ -15,13 +15,24 @@ use tower_lsp::LanguageServer;
 use tower_lsp::LspService;
 use tower_lsp::Server;
 
+use crate::cancellation::GenerationTracker;
+use crate::code_action::FixCollection;
+use crate::diagnostics_gate::DiagnosticsChannel;
+use crate::flycheck::{DiagnosticCollection, FlycheckHandle};
 use crate::handlers;
 use crate::r_env;
-use crate::state::WorldState;
+use crate::state::{WorldSnapshot, WorldState};
 
 pub struct Backend {
     client: Client,
     state: Arc<RwLock<WorldState>>,
+    /// Native (tree-sitter) and external (lintr) diagnostics, merged before publishing.
+    diagnostics: Arc<DiagnosticCollection>,
+    flycheck: FlycheckHandle,
+    /// Quick-fixes attached to the native diagnostics, keyed by URI.
+    fixes: Arc<FixCollection>,
+    /// Per-document generations, used to cancel stale in-flight handlers.
+    generations: GenerationTracker,
 }
 
 impl Backend {
@@ -29,11 +40,100 @@ impl Backend {
         let library_paths = r_env::find_library_paths();
         log::info!("Discovered R library paths: {:?}", library_paths);
 
+        let state = Arc::new(RwLock::new(WorldState::new(library_paths)));
+        let diagnostics = Arc::new(DiagnosticCollection::new());
+
+        let flycheck = {
+            let state = state.clone();
+            let diagnostics = diagnostics.clone();
+            let client = client.clone();
+            FlycheckHandle::spawn(move |uri, version, lint_diagnostics| {
+                let state = state.clone();
+                let diagnostics = diagnostics.clone();
+                let client = client.clone();
+                tokio::spawn(async move {
+                    let current_state = state.read().await;
+
+                    // The document may have been closed while lintr was
+                    // still running; don't resurrect diagnostics for a file
+                    // the client no longer considers open.
+                    if !current_state.documents.contains_key(&uri) {
+                        log::trace!("Discarding lintr result for closed document: {}", uri);
+                        return;
+                    }
+
+                    // Gated on the External channel, independent of the
+                    // Native publish for the same version: lintr runs async
+                    // and frequently lands after the native pass has already
+                    // published the same version, so sharing a gate with it
+                    // would discard every lintr result as "stale".
+                    if let Some(ver) = version {
+                        if !current_state
+                            .diagnostics_gate
+                            .can_publish(&uri, ver, DiagnosticsChannel::External)
+                        {
+                            log::trace!("Discarding stale lintr result for {}", uri);
+                            return;
+                        }
+                        current_state.diagnostics_gate.record_publish(
+                            &uri,
+                            ver,
+                            DiagnosticsChannel::External,
+                        );
+                    }
+                    drop(current_state);
+
+                    diagnostics.set_external(uri.clone(), lint_diagnostics);
+                    let combined = diagnostics.combined(&uri);
+                    client.publish_diagnostics(uri, combined, None).await;
+                });
+            })
+        };
+
         Self {
             client,
-            state: Arc::new(RwLock::new(WorldState::new(library_paths))),
+            state,
+            diagnostics,
+            flycheck,
+            fixes: Arc::new(FixCollection::new()),
+            generations: GenerationTracker::new(),
         }
     }
+
+    /// Run `f` against a point-in-time snapshot of world state for `uri`,
+    /// off the async executor, returning `None` if the document's
+    /// generation has moved on by the time `f` would run.
+    ///
+    /// Read-only requests (hover, completion, goto-definition, ...) use
+    /// this instead of holding `self.state`'s read lock for the duration
+    /// of the computation: the snapshot is captured and the lock released
+    /// immediately, the actual analysis runs on a blocking thread so it
+    /// can't stall other requests or hold up `did_change`, and the result
+    /// is discarded if a newer edit superseded it while `f` was running.
+    async fn with_snapshot<T, F>(&self, uri: &Url, f: F) -> Option<T>
+    where
+        F: FnOnce(&WorldSnapshot) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let token = self.generations.token(uri);
+        let snapshot = {
+            let state = self.state.read().await;
+            state.snapshot()
+        };
+
+        tokio::task::spawn_blocking(move || {
+            if token.is_cancelled() {
+                return None;
+            }
+            let result = f(&snapshot);
+            if token.is_cancelled() {
+                return None;
+            }
+            Some(result)
+        })
+        .await
+        .unwrap_or(None)
+    }
 }
 
 #[tower_lsp::async_trait]
@@ -82,6 +182,7 @@ impl LanguageServer for Backend {
                     first_trigger_character: String::from("\n"),
                     more_trigger_character: None,
                 }),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
                 ..Default::default()
             },
             server_info: Some(ServerInfo {
@@ -121,34 +222,56 @@ impl LanguageServer for Backend {
         let uri = params.text_document.uri;
         let version = params.text_document.version;
 
+        // Bump this document's generation so any handler already in
+        // flight for the old text notices and bails out instead of
+        // racing this edit to the client.
+        self.generations.bump(&uri);
+
         // Compute affected files while holding write lock
         let affected_uris = {
             let mut state = self.state.write().await;
             if let Some(doc) = state.documents.get_mut(&uri) {
-                doc.version = Some(version);
+                Arc::make_mut(doc).version = Some(version);
             }
+
+            // Snapshot the export-surface hash before the edit so we can
+            // tell afterward whether this change touched the directives the
+            // hash covers (sourced paths, call sites, working directory).
+            // Top-level definitions are not part of the hash, so an edit
+            // that only adds, removes or changes one does not cascade.
+            let old_hash = state.cross_file_meta.get(&uri).map(|m| m.export_surface_hash.clone());
+
             for change in params.content_changes {
                 state.apply_change(&uri, change);
             }
             // Record as recently changed for activity prioritization
             state.cross_file_activity.record_recent(uri.clone());
-            
+
+            let new_hash = state.cross_file_meta.get(&uri).map(|m| m.export_surface_hash.clone());
+
             // Compute affected files from dependency graph
             let mut affected: Vec<Url> = vec![uri.clone()];
-            let dependents = state.cross_file_graph.get_transitive_dependents(
-                &uri,
-                state.cross_file_config.max_chain_depth,
-            );
-            // Filter to only open documents and mark for force republish
-            for dep in dependents {
-                if state.documents.contains_key(&dep) {
-                    // Mark dependent files for force republish (Requirement 0.8)
-                    // This allows same-version republish when dependency changes
-                    state.diagnostics_gate.mark_force_republish(&dep);
-                    affected.push(dep);
+
+            // Only cascade to dependents when the export surface actually
+            // changed (sourced paths, call sites, working directory). A
+            // keystroke inside a function body shouldn't republish
+            // diagnostics for every file that sources this one.
+            if old_hash != new_hash {
+                let dependents = state.cross_file_graph.get_transitive_dependents(
+                    &uri,
+                    state.cross_file_config.max_chain_depth,
+                );
+                // Filter to only open documents and mark for force republish
+                for dep in dependents {
+                    if state.documents.contains_key(&dep) {
+                        // Mark dependent files for force republish (Requirement 0.8)
+                        // This allows same-version republish when dependency changes
+                        state.diagnostics_gate.mark_force_republish(&dep);
+                        affected.push(dep);
+                    }
                 }
             }
-            
+
             // Prioritize by activity (trigger first, then active, then visible, then recent)
             let activity = &state.cross_file_activity;
             affected.sort_by_key(|u| {
@@ -191,6 +314,11 @@ impl LanguageServer for Backend {
         
         // Close the document
         state.close_document(uri);
+        drop(state);
+
+        self.diagnostics.clear(uri);
+        self.fixes.clear(uri);
+        self.generations.remove(uri);
     }
 
     async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
@@ -208,17 +336,41 @@ impl LanguageServer for Backend {
             
             match change.typ {
                 FileChangeType::CREATED | FileChangeType::CHANGED => {
-                    // Invalidate disk-backed caches
+                    // Snapshot the export-surface hash before re-parsing,
+                    // same as did_change, so we only cascade to dependents
+                    // when the edit changed the file's directives (edits to
+                    // top-level definitions alone do not cascade).
+                    let old_hash = state.cross_file_meta.get(uri).map(|m| m.export_surface_hash.clone());
+
+                    // Invalidate disk-backed caches, then actually re-read
+                    // the file's new contents so `cross_file_meta` (and the
+                    // dependency graph built from it) reflect what's on disk
+                    // now, not whatever this file looked like when it was
+                    // last parsed.
                     state.cross_file_file_cache.invalidate(uri);
                     state.cross_file_workspace_index.invalidate(uri);
+                    state.reparse_from_disk(uri);
                     log::trace!("Invalidated caches for changed file: {}", uri);
+
+                    let new_hash = state.cross_file_meta.get(uri).map(|m| m.export_surface_hash.clone());
+
+                    if old_hash != new_hash {
+                        let dependents = state.cross_file_graph.get_transitive_dependents(
+                            uri,
+                            state.cross_file_config.max_chain_depth,
+                        );
+                        for dep in dependents {
+                            state.cross_file_file_cache.invalidate(&dep);
+                            state.cross_file_workspace_index.invalidate(&dep);
+                        }
+                    }
                 }
                 FileChangeType::DELETED => {
                     // Remove from dependency graph and caches
                     state.cross_file_graph.remove_file(uri);
                     state.cross_file_file_cache.invalidate(uri);
                     state.cross_file_workspace_index.invalidate(uri);
-                    state.cross_file_cache.invalidate(uri);
+                    state.cross_file_cache.remove(uri);
                     state.cross_file_meta.remove(uri);
                     log::trace!("Removed deleted file from cross-file state: {}", uri);
                 }
@@ -232,88 +384,122 @@ impl LanguageServer for Backend {
     }
 
     async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
-        let state = self.state.read().await;
-        Ok(handlers::folding_range(&state, &params.text_document.uri))
+        let uri = params.text_document.uri;
+        Ok(self
+            .with_snapshot(&uri, move |snapshot| {
+                handlers::folding_range(snapshot, &uri)
+            })
+            .await
+            .flatten())
     }
 
     async fn selection_range(
         &self,
         params: SelectionRangeParams,
     ) -> Result<Option<Vec<SelectionRange>>> {
-        let state = self.state.read().await;
-        Ok(handlers::selection_range(
-            &state,
-            &params.text_document.uri,
-            params.positions,
-        ))
+        let uri = params.text_document.uri;
+        let positions = params.positions;
+        Ok(self
+            .with_snapshot(&uri, move |snapshot| {
+                handlers::selection_range(snapshot, &uri, positions)
+            })
+            .await
+            .flatten())
     }
 
     async fn document_symbol(
         &self,
         params: DocumentSymbolParams,
     ) -> Result<Option<DocumentSymbolResponse>> {
-        let state = self.state.read().await;
-        Ok(handlers::document_symbol(&state, &params.text_document.uri))
+        let uri = params.text_document.uri;
+        Ok(self
+            .with_snapshot(&uri, move |snapshot| {
+                handlers::document_symbol(snapshot, &uri)
+            })
+            .await
+            .flatten())
     }
 
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
-        let state = self.state.read().await;
-        Ok(handlers::completion(
-            &state,
-            &params.text_document_position.text_document.uri,
-            params.text_document_position.position,
-        ))
+        let uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        Ok(self
+            .with_snapshot(&uri, move |snapshot| {
+                handlers::completion(snapshot, &uri, position)
+            })
+            .await
+            .flatten())
     }
 
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
-        let state = self.state.read().await;
-        Ok(handlers::hover(
-            &state,
-            &params.text_document_position_params.text_document.uri,
-            params.text_document_position_params.position,
-        ))
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        Ok(self
+            .with_snapshot(&uri, move |snapshot| handlers::hover(snapshot, &uri, position))
+            .await
+            .flatten())
     }
 
     async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
-        let state = self.state.read().await;
-        Ok(handlers::signature_help(
-            &state,
-            &params.text_document_position_params.text_document.uri,
-            params.text_document_position_params.position,
-        ))
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        Ok(self
+            .with_snapshot(&uri, move |snapshot| {
+                handlers::signature_help(snapshot, &uri, position)
+            })
+            .await
+            .flatten())
     }
 
     async fn goto_definition(
         &self,
         params: GotoDefinitionParams,
     ) -> Result<Option<GotoDefinitionResponse>> {
-        let state = self.state.read().await;
-        Ok(handlers::goto_definition(
-            &state,
-            &params.text_document_position_params.text_document.uri,
-            params.text_document_position_params.position,
-        ))
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        Ok(self
+            .with_snapshot(&uri, move |snapshot| {
+                handlers::goto_definition(snapshot, &uri, position)
+            })
+            .await
+            .flatten())
     }
 
     async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
-        let state = self.state.read().await;
-        Ok(handlers::references(
-            &state,
-            &params.text_document_position.text_document.uri,
-            params.text_document_position.position,
-        ))
+        let uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        Ok(self
+            .with_snapshot(&uri, move |snapshot| {
+                handlers::references(snapshot, &uri, position)
+            })
+            .await
+            .flatten())
     }
 
     async fn on_type_formatting(
         &self,
         params: DocumentOnTypeFormattingParams,
     ) -> Result<Option<Vec<TextEdit>>> {
-        let state = self.state.read().await;
-        Ok(handlers::on_type_formatting(
-            &state,
-            &params.text_document_position.text_document.uri,
-            params.text_document_position.position,
-        ))
+        let uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        Ok(self
+            .with_snapshot(&uri, move |snapshot| {
+                handlers::on_type_formatting(snapshot, &uri, position)
+            })
+            .await
+            .flatten())
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = &params.text_document.uri;
+        let only = params.context.only.as_deref();
+        let actions = self.fixes.overlapping(uri, params.range, only);
+
+        if actions.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(actions))
+        }
     }
 }
 
@@ -322,24 +508,37 @@ impl Backend {
         let state = self.state.read().await;
         let version = state.documents.get(uri).and_then(|d| d.version);
         
-        // Check if we can publish (monotonic gate)
+        // Check if we can publish (monotonic gate, Native channel)
         if let Some(ver) = version {
-            if !state.diagnostics_gate.can_publish(uri, ver) {
+            if !state
+                .diagnostics_gate
+                .can_publish(uri, ver, DiagnosticsChannel::Native)
+            {
                 log::trace!("Skipping diagnostics for {}: monotonic gate", uri);
                 return;
             }
         }
-        
-        let diagnostics = handlers::diagnostics(&state, uri);
-        
+
+        let native_diagnostics = handlers::diagnostics(&state, uri);
+        let fixes = handlers::code_action_fixes(&state, uri);
+
         // Record the publish (uses interior mutability, no write lock needed)
         if let Some(ver) = version {
-            state.diagnostics_gate.record_publish(uri, ver);
+            state
+                .diagnostics_gate
+                .record_publish(uri, ver, DiagnosticsChannel::Native);
         }
-        
+
         drop(state);
-        
-        self.client.publish_diagnostics(uri.clone(), diagnostics, None).await;
+
+        self.diagnostics.set_native(uri.clone(), native_diagnostics);
+        self.fixes.set(uri.clone(), fixes);
+        if let Ok(path) = uri.to_file_path() {
+            self.flycheck.request(uri.clone(), path, version);
+        }
+
+        let combined = self.diagnostics.combined(uri);
+        self.client.publish_diagnostics(uri.clone(), combined, None).await;
     }
 }
 
@@ -0,0 +1,144 @@
+//
+// cancellation.rs
+//
+// Per-document generation counters used to cancel in-flight request
+// handlers once their document has moved on.
+//
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use tower_lsp::lsp_types::Url;
+
+/// Per-document generation counters, bumped on every `did_change`.
+///
+/// A handler captures a [`CancellationToken`] for its document when it
+/// starts. If the generation has moved by the time the handler's
+/// `spawn_blocking` computation finishes (or checks mid-flight), the
+/// result is stale - the client has since sent a newer edit - and the
+/// handler should return `Ok(None)` instead of racing that edit to the
+/// client.
+///
+/// This only covers the "document changed under you" case. It does not
+/// index tokens by LSP request id, so it can't yet honor an explicit
+/// `$/cancelRequest` for a specific in-flight request; tower_lsp already
+/// drops that request's future on its own when the client cancels it.
+#[derive(Debug, Default)]
+pub struct GenerationTracker {
+    generations: RwLock<HashMap<Url, Arc<AtomicU64>>>,
+}
+
+impl GenerationTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn counter(&self, uri: &Url) -> Arc<AtomicU64> {
+        if let Some(counter) = self.generations.read().unwrap().get(uri) {
+            return counter.clone();
+        }
+        self.generations
+            .write()
+            .unwrap()
+            .entry(uri.clone())
+            .or_insert_with(|| Arc::new(AtomicU64::new(0)))
+            .clone()
+    }
+
+    /// Advance `uri`'s generation. Call this from `did_change` before
+    /// dispatching revalidation, so any handler already reading the old
+    /// generation observes a mismatch.
+    pub fn bump(&self, uri: &Url) {
+        self.counter(uri).fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// A token capturing `uri`'s generation right now, for a handler that
+    /// is about to start computing a response for it.
+    pub fn token(&self, uri: &Url) -> CancellationToken {
+        let counter = self.counter(uri);
+        let observed = counter.load(Ordering::SeqCst);
+        CancellationToken { counter, observed }
+    }
+
+    pub fn remove(&self, uri: &Url) {
+        self.generations.write().unwrap().remove(uri);
+    }
+}
+
+/// Whether the handler that requested this token should still bother
+/// producing (or sending) a result: `false` once its document's
+/// generation has moved past what it observed at creation time.
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    counter: Arc<AtomicU64>,
+    observed: u64,
+}
+
+impl CancellationToken {
+    pub fn is_cancelled(&self) -> bool {
+        self.counter.load(Ordering::SeqCst) != self.observed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_uri() -> Url {
+        Url::parse("file:///test.R").unwrap()
+    }
+
+    #[test]
+    fn fresh_token_is_not_cancelled() {
+        let tracker = GenerationTracker::new();
+        let uri = test_uri();
+
+        let token = tracker.token(&uri);
+        assert!(!token.is_cancelled());
+    }
+
+    #[test]
+    fn bump_cancels_outstanding_tokens() {
+        let tracker = GenerationTracker::new();
+        let uri = test_uri();
+
+        let token = tracker.token(&uri);
+        tracker.bump(&uri);
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn bump_does_not_affect_tokens_taken_afterward() {
+        let tracker = GenerationTracker::new();
+        let uri = test_uri();
+
+        tracker.bump(&uri);
+        let token = tracker.token(&uri);
+        assert!(!token.is_cancelled());
+    }
+
+    #[test]
+    fn bump_only_cancels_the_named_document() {
+        let tracker = GenerationTracker::new();
+        let uri = test_uri();
+        let other = Url::parse("file:///other.R").unwrap();
+
+        let token = tracker.token(&other);
+        tracker.bump(&uri);
+        assert!(!token.is_cancelled());
+    }
+
+    #[test]
+    fn remove_resets_the_generation() {
+        let tracker = GenerationTracker::new();
+        let uri = test_uri();
+
+        tracker.bump(&uri);
+        tracker.remove(&uri);
+        // A fresh token taken after remove() observes generation 0 again,
+        // same as a document the tracker has never seen.
+        let token = tracker.token(&uri);
+        assert!(!token.is_cancelled());
+    }
+}
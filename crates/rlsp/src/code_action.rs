@@ -0,0 +1,177 @@
+//
+// code_action.rs
+//
+// Quick-fixes attached to diagnostics, surfaced via textDocument/codeAction
+//
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Position, Range, Url,
+};
+
+/// A fix attached to a diagnostic at `range`, handed back verbatim when a
+/// `textDocument/codeAction` request overlaps it.
+#[derive(Debug, Clone)]
+pub struct Fix {
+    pub range: Range,
+    pub action: CodeActionOrCommand,
+}
+
+/// Fixes produced alongside the native diagnostics for each open document,
+/// keyed by URI, populated at the same time as the diagnostics themselves.
+#[derive(Debug, Default)]
+pub struct FixCollection {
+    inner: RwLock<HashMap<Url, Vec<Fix>>>,
+}
+
+impl FixCollection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, uri: Url, fixes: Vec<Fix>) {
+        if let Ok(mut inner) = self.inner.write() {
+            inner.insert(uri, fixes);
+        }
+    }
+
+    pub fn clear(&self, uri: &Url) {
+        if let Ok(mut inner) = self.inner.write() {
+            inner.remove(uri);
+        }
+    }
+
+    /// Fixes for `uri` whose range overlaps `range`, filtered to the
+    /// requested `only` kinds (no filter if the client didn't specify any).
+    pub fn overlapping(
+        &self,
+        uri: &Url,
+        range: Range,
+        only: Option<&[CodeActionKind]>,
+    ) -> Vec<CodeActionOrCommand> {
+        let Ok(inner) = self.inner.read() else {
+            return Vec::new();
+        };
+        let Some(fixes) = inner.get(uri) else {
+            return Vec::new();
+        };
+
+        fixes
+            .iter()
+            .filter(|fix| ranges_overlap(fix.range, range))
+            .filter(|fix| matches_requested_kind(fix, only))
+            .map(|fix| fix.action.clone())
+            .collect()
+    }
+}
+
+fn position_le(a: Position, b: Position) -> bool {
+    (a.line, a.character) <= (b.line, b.character)
+}
+
+fn ranges_overlap(a: Range, b: Range) -> bool {
+    position_le(a.start, b.end) && position_le(b.start, a.end)
+}
+
+fn matches_requested_kind(fix: &Fix, only: Option<&[CodeActionKind]>) -> bool {
+    let Some(only) = only else {
+        return true;
+    };
+    match &fix.action {
+        CodeActionOrCommand::CodeAction(action) => {
+            action.kind.as_ref().is_some_and(|kind| only.contains(kind))
+        }
+        // Plain commands aren't tagged with a kind; don't filter them out.
+        CodeActionOrCommand::Command(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start_line: u32, end_line: u32) -> Range {
+        Range::new(Position::new(start_line, 0), Position::new(end_line, 0))
+    }
+
+    fn fix_with_kind(r: Range, kind: Option<CodeActionKind>) -> Fix {
+        Fix {
+            range: r,
+            action: CodeActionOrCommand::CodeAction(CodeAction {
+                title: "fix".to_string(),
+                kind,
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn overlapping_ranges_overlap() {
+        assert!(ranges_overlap(range(1, 3), range(2, 4)));
+    }
+
+    #[test]
+    fn touching_ranges_overlap() {
+        assert!(ranges_overlap(range(1, 2), range(2, 3)));
+    }
+
+    #[test]
+    fn disjoint_ranges_do_not_overlap() {
+        assert!(!ranges_overlap(range(1, 2), range(3, 4)));
+    }
+
+    #[test]
+    fn no_requested_kind_matches_everything() {
+        let fix = fix_with_kind(range(0, 1), Some(CodeActionKind::QUICKFIX));
+        assert!(matches_requested_kind(&fix, None));
+    }
+
+    #[test]
+    fn requested_kind_matches_same_kind() {
+        let fix = fix_with_kind(range(0, 1), Some(CodeActionKind::QUICKFIX));
+        assert!(matches_requested_kind(
+            &fix,
+            Some(&[CodeActionKind::QUICKFIX])
+        ));
+    }
+
+    #[test]
+    fn requested_kind_rejects_different_kind() {
+        let fix = fix_with_kind(range(0, 1), Some(CodeActionKind::QUICKFIX));
+        assert!(!matches_requested_kind(
+            &fix,
+            Some(&[CodeActionKind::REFACTOR])
+        ));
+    }
+
+    #[test]
+    fn commands_are_never_filtered_by_kind() {
+        let fix = Fix {
+            range: range(0, 1),
+            action: CodeActionOrCommand::Command(tower_lsp::lsp_types::Command {
+                title: "run".to_string(),
+                command: "noop".to_string(),
+                arguments: None,
+            }),
+        };
+        assert!(matches_requested_kind(&fix, Some(&[CodeActionKind::QUICKFIX])));
+    }
+
+    #[test]
+    fn overlapping_filters_to_the_requested_range() {
+        let collection = FixCollection::new();
+        let uri = Url::parse("file:///test.R").unwrap();
+        collection.set(
+            uri.clone(),
+            vec![
+                fix_with_kind(range(0, 1), Some(CodeActionKind::QUICKFIX)),
+                fix_with_kind(range(5, 6), Some(CodeActionKind::QUICKFIX)),
+            ],
+        );
+
+        let actions = collection.overlapping(&uri, range(0, 1), None);
+        assert_eq!(actions.len(), 1);
+    }
+}
@@ -0,0 +1,9 @@
+//
+// cross_file/cache.rs
+//
+// Re-exports raven's metadata cache types under the cross_file module path
+//
+
+pub use raven::cross_file::cache::{
+    MetadataCache, MetadataStore, ParentResolution, RedbMetadataStore, METADATA_SCHEMA_VERSION,
+};
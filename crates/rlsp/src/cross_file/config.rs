@@ -0,0 +1,69 @@
+//
+// cross_file/config.rs
+//
+// User-configurable knobs for cross-file awareness
+//
+
+use serde::{Deserialize, Serialize};
+
+/// How to display a resolved cross-file target (`sourced_by`/`sources`
+/// entries) in hover text and diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PathDisplayMode {
+    /// Relative to the current file's directory, e.g. `../lib/helpers.R`.
+    FileRelative,
+    /// Relative to the workspace root, e.g. `lib/helpers.R`.
+    WorkspaceRootRelative,
+}
+
+impl Default for PathDisplayMode {
+    fn default() -> Self {
+        PathDisplayMode::FileRelative
+    }
+}
+
+/// Where the metadata cache persists entries between server restarts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "kind")]
+pub enum MetadataStoreBackend {
+    /// No persistence: re-index from scratch on every restart.
+    Memory,
+    /// Persist to an embedded `redb` database at the given path.
+    Redb { path: std::path::PathBuf },
+}
+
+impl Default for MetadataStoreBackend {
+    fn default() -> Self {
+        MetadataStoreBackend::Memory
+    }
+}
+
+/// User-configurable limits and behavior for cross-file awareness.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossFileConfig {
+    /// Maximum depth to follow when walking the dependency graph for
+    /// transitive-dependent revalidation on `did_change`.
+    pub max_chain_depth: usize,
+    /// Cap on how many files get revalidated/republished per trigger
+    /// (edit, watched-file change, ...) to bound revalidation storms.
+    pub max_revalidations_per_trigger: usize,
+    /// How resolved cross-file targets are to be displayed. Nothing in the
+    /// server reads this setting: hover and diagnostic text do not show
+    /// cross-file targets.
+    pub path_display_mode: PathDisplayMode,
+    /// Where the metadata cache persists entries on disk, if at all.
+    pub metadata_store: MetadataStoreBackend,
+}
+
+impl Default for CrossFileConfig {
+    fn default() -> Self {
+        Self {
+            max_chain_depth: 10,
+            max_revalidations_per_trigger: 50,
+            path_display_mode: PathDisplayMode::default(),
+            metadata_store: MetadataStoreBackend::default(),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//
+// cross_file/content_provider.rs
+//
+// Reading a URI's text whether or not it's currently an open document
+//
+
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::Url;
+
+use crate::state::Document;
+
+/// Where to read a cross-file target's text from: the open-document map if
+/// the client has it open, disk otherwise. A cross-file target is frequently
+/// a file the client hasn't opened (e.g. a shared helper script only ever
+/// `source()`d, never edited directly), so handlers that need to read a
+/// dependency's text shouldn't assume it's in `documents`.
+pub trait ContentProvider {
+    fn read(&self, uri: &Url) -> Option<String>;
+}
+
+/// Prefers an open document's in-memory text (which may have unsaved
+/// changes the disk copy doesn't); falls back to reading the file at `uri`
+/// from disk.
+pub struct DocumentOrDiskContentProvider<'a> {
+    pub documents: &'a HashMap<Url, Document>,
+}
+
+impl ContentProvider for DocumentOrDiskContentProvider<'_> {
+    fn read(&self, uri: &Url) -> Option<String> {
+        if let Some(doc) = self.documents.get(uri) {
+            return Some(doc.text.clone());
+        }
+        std::fs::read_to_string(uri.to_file_path().ok()?).ok()
+    }
+}
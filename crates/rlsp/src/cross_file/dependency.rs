@@ -0,0 +1,147 @@
+//
+// cross_file/dependency.rs
+//
+// Directed graph of which files source/run/include which other files
+//
+
+use std::collections::{HashMap, HashSet};
+
+use tower_lsp::lsp_types::Url;
+
+/// Tracks the directed "sources/is sourced by" relationship between files,
+/// so a change to one file's export surface can be cascaded to every file
+/// that transitively depends on it.
+///
+/// Edges point from a dependent to its dependencies (what it sources); the
+/// reverse index is kept alongside so [`get_transitive_dependents`] doesn't
+/// have to scan every entry on each call.
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    /// uri -> files it directly sources/depends on.
+    dependencies: HashMap<Url, HashSet<Url>>,
+    /// uri -> files that directly depend on it.
+    dependents: HashMap<Url, HashSet<Url>>,
+}
+
+impl DependencyGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace `uri`'s outgoing edges (the files it sources) with `targets`,
+    /// updating the reverse index to match.
+    pub fn set_dependencies(&mut self, uri: &Url, targets: impl IntoIterator<Item = Url>) {
+        if let Some(old) = self.dependencies.remove(uri) {
+            for dep in old {
+                if let Some(back) = self.dependents.get_mut(&dep) {
+                    back.remove(uri);
+                }
+            }
+        }
+
+        let targets: HashSet<Url> = targets.into_iter().filter(|t| t != uri).collect();
+        for dep in &targets {
+            self.dependents.entry(dep.clone()).or_default().insert(uri.clone());
+        }
+        self.dependencies.insert(uri.clone(), targets);
+    }
+
+    /// Drop `uri` entirely: its outgoing edges and anything pointing at it.
+    pub fn remove_file(&mut self, uri: &Url) {
+        self.set_dependencies(uri, std::iter::empty());
+        self.dependencies.remove(uri);
+        self.dependents.remove(uri);
+    }
+
+    /// Every file that transitively depends on `uri` (sources it, directly
+    /// or through another file), up to `max_depth` hops, excluding `uri`
+    /// itself.
+    pub fn get_transitive_dependents(&self, uri: &Url, max_depth: usize) -> Vec<Url> {
+        // Seeded with `uri` so a cycle back to it isn't walked or reported.
+        let mut seen = HashSet::from([uri.clone()]);
+        let mut frontier = vec![uri.clone()];
+
+        for _ in 0..max_depth {
+            let mut next = Vec::new();
+            for node in &frontier {
+                let Some(direct) = self.dependents.get(node) else {
+                    continue;
+                };
+                for dep in direct {
+                    if seen.insert(dep.clone()) {
+                        next.push(dep.clone());
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+
+        seen.remove(uri);
+        seen.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri(name: &str) -> Url {
+        Url::parse(&format!("file:///{}", name)).unwrap()
+    }
+
+    #[test]
+    fn direct_dependent_is_reported() {
+        let mut graph = DependencyGraph::new();
+        graph.set_dependencies(&uri("child.R"), [uri("parent.R")]);
+
+        let dependents = graph.get_transitive_dependents(&uri("parent.R"), 10);
+        assert_eq!(dependents, vec![uri("child.R")]);
+    }
+
+    #[test]
+    fn transitive_dependent_is_reported_within_depth() {
+        let mut graph = DependencyGraph::new();
+        graph.set_dependencies(&uri("b.R"), [uri("a.R")]);
+        graph.set_dependencies(&uri("c.R"), [uri("b.R")]);
+
+        let dependents = graph.get_transitive_dependents(&uri("a.R"), 10);
+        let mut dependents = dependents;
+        dependents.sort_by_key(|u| u.to_string());
+        assert_eq!(dependents, vec![uri("b.R"), uri("c.R")]);
+    }
+
+    #[test]
+    fn max_depth_bounds_the_walk() {
+        let mut graph = DependencyGraph::new();
+        graph.set_dependencies(&uri("b.R"), [uri("a.R")]);
+        graph.set_dependencies(&uri("c.R"), [uri("b.R")]);
+
+        let dependents = graph.get_transitive_dependents(&uri("a.R"), 1);
+        assert_eq!(dependents, vec![uri("b.R")]);
+    }
+
+    #[test]
+    fn remove_file_drops_its_edges() {
+        let mut graph = DependencyGraph::new();
+        graph.set_dependencies(&uri("b.R"), [uri("a.R")]);
+        graph.remove_file(&uri("b.R"));
+
+        assert!(graph.get_transitive_dependents(&uri("a.R"), 10).is_empty());
+    }
+
+    #[test]
+    fn set_dependencies_replaces_previous_edges() {
+        let mut graph = DependencyGraph::new();
+        graph.set_dependencies(&uri("b.R"), [uri("a.R")]);
+        graph.set_dependencies(&uri("b.R"), [uri("z.R")]);
+
+        assert!(graph.get_transitive_dependents(&uri("a.R"), 10).is_empty());
+        assert_eq!(
+            graph.get_transitive_dependents(&uri("z.R"), 10),
+            vec![uri("b.R")]
+        );
+    }
+}
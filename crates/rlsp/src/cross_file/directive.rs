@@ -0,0 +1,153 @@
+//
+// cross_file/directive.rs
+//
+// Parsing `@lsp-*` cross-file awareness directives out of R comments
+//
+
+use super::path_resolve::RelPathBuf;
+use super::types::{BackwardDirective, CallSiteSpec, CrossFileMetadata, ForwardDirective};
+
+/// Synonyms for a backward (`@lsp-sourced-by`-family) directive, longest
+/// first so a prefix synonym can't shadow a longer one sharing its start.
+const BACKWARD_KEYWORDS: &[&str] = &["@lsp-included-by", "@lsp-sourced-by", "@lsp-run-by"];
+
+const FORWARD_KEYWORDS: &[&str] = &["@lsp-source"];
+
+/// Working-directory directive synonyms, longest-prefix-first for the same
+/// reason as [`BACKWARD_KEYWORDS`] (`@lsp-working-dir` is a prefix of
+/// `@lsp-working-directory`, `@lsp-current-dir` of `@lsp-current-directory`).
+const WORKING_DIRECTORY_KEYWORDS: &[&str] = &[
+    "@lsp-working-directory",
+    "@lsp-working-dir",
+    "@lsp-current-directory",
+    "@lsp-current-dir",
+    "@lsp-cd",
+    "@lsp-wd",
+];
+
+/// Parse every `@lsp-*` directive out of `text`'s `#` comments into a single
+/// [`CrossFileMetadata`]. Lines that aren't directives (including ordinary
+/// comments and code) are silently ignored; a directive with a missing or
+/// unusable path is dropped rather than rejecting the whole file, since one
+/// bad comment shouldn't take down analysis for the rest of the document.
+/// An unparseable `line=` falls back to `match=`, or else to the default
+/// call site.
+///
+/// Does not populate [`CrossFileMetadata::export_surface_hash`]; callers
+/// compute that separately via [`super::export_surface_hash`] once they
+/// have the full parse, since it deliberately excludes some of these
+/// fields.
+pub fn parse_directives(text: &str) -> CrossFileMetadata {
+    let mut meta = CrossFileMetadata::default();
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let Some(comment) = comment_text(line) else {
+            continue;
+        };
+
+        if strip_keyword(comment, &["@lsp-ignore-next"]).is_some() {
+            meta.ignored_next_lines.push(line_idx as u32 + 1);
+            continue;
+        }
+        if strip_keyword(comment, &["@lsp-ignore"]).is_some() {
+            meta.ignored_lines.push(line_idx as u32);
+            continue;
+        }
+        if let Some(rest) = strip_keyword(comment, BACKWARD_KEYWORDS) {
+            if let Some(directive) = parse_backward_directive(rest) {
+                meta.sourced_by.push(directive);
+            }
+            continue;
+        }
+        if let Some(rest) = strip_keyword(comment, FORWARD_KEYWORDS) {
+            if let Some((path, _)) = take_path_token(rest) {
+                if let Ok(path) = RelPathBuf::try_new(path) {
+                    meta.sources.push(ForwardDirective { path });
+                }
+            }
+            continue;
+        }
+        if let Some(rest) = strip_keyword(comment, WORKING_DIRECTORY_KEYWORDS) {
+            if let Some((path, _)) = take_path_token(rest) {
+                if let Ok(path) = RelPathBuf::try_new(path) {
+                    meta.working_directory = Some(path);
+                }
+            }
+        }
+    }
+
+    meta
+}
+
+/// The text of the first `#` comment on `line`, if any. Doesn't attempt to
+/// distinguish a `#` inside a string literal from a real comment marker -
+/// directives are only ever meaningful as actual comments, and an R string
+/// containing a literal `@lsp-` directive-shaped substring is vanishingly
+/// unlikely.
+fn comment_text(line: &str) -> Option<&str> {
+    line.find('#').map(|idx| line[idx + 1..].trim())
+}
+
+/// Strip the first matching keyword from `comment`, along with an optional
+/// trailing `:`, requiring the keyword end on a word boundary (end of
+/// string or whitespace) so e.g. `@lsp-source` can't match inside
+/// `@lsp-sourced-by`.
+fn strip_keyword<'a>(comment: &'a str, keywords: &[&str]) -> Option<&'a str> {
+    for keyword in keywords {
+        let Some(rest) = comment.strip_prefix(keyword) else {
+            continue;
+        };
+        let rest = rest.strip_prefix(':').unwrap_or(rest);
+        if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+            return Some(rest);
+        }
+    }
+    None
+}
+
+fn parse_backward_directive(rest: &str) -> Option<BackwardDirective> {
+    let (path, remainder) = take_path_token(rest)?;
+    let path = RelPathBuf::try_new(path).ok()?;
+
+    let call_site = if let Some(line) = extract_line_param(remainder) {
+        CallSiteSpec::Line(line.saturating_sub(1))
+    } else if let Some(pattern) = extract_match_param(remainder) {
+        CallSiteSpec::Match(pattern)
+    } else {
+        CallSiteSpec::Default
+    };
+
+    Some(BackwardDirective { path, call_site })
+}
+
+/// Take a single path-shaped token off the front of `s`: a `"`/`'`-quoted
+/// span (allowing embedded whitespace), or otherwise everything up to the
+/// next whitespace. Returns the token and whatever follows it.
+fn take_path_token(s: &str) -> Option<(String, &str)> {
+    let s = s.trim_start();
+    if let Some(rest) = s.strip_prefix('"') {
+        let end = rest.find('"')?;
+        return Some((rest[..end].to_string(), &rest[end + 1..]));
+    }
+    if let Some(rest) = s.strip_prefix('\'') {
+        let end = rest.find('\'')?;
+        return Some((rest[..end].to_string(), &rest[end + 1..]));
+    }
+    if s.is_empty() {
+        return None;
+    }
+    let end = s.find(char::is_whitespace).unwrap_or(s.len());
+    Some((s[..end].to_string(), &s[end..]))
+}
+
+fn extract_line_param(rest: &str) -> Option<u32> {
+    let after = rest.split("line=").nth(1)?;
+    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+    after[..end].parse().ok()
+}
+
+fn extract_match_param(rest: &str) -> Option<String> {
+    let after = rest.split("match=").nth(1)?;
+    let (pattern, _) = take_path_token(after)?;
+    Some(pattern)
+}
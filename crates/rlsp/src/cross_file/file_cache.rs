@@ -0,0 +1,57 @@
+//
+// cross_file/file_cache.rs
+//
+// Cached on-disk content for cross-file dependencies the client hasn't opened
+//
+
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::Url;
+
+/// Contents read from disk for a file a directive points at that isn't
+/// (or isn't yet) an open document, so resolving the same dependency twice
+/// doesn't re-read the file each time.
+///
+/// Entries are populated by whatever resolves a directive target and need
+/// its content (e.g. backward-directive call-site lookups); this type only
+/// owns the cache, not the read itself.
+#[derive(Debug, Default)]
+pub struct FileCache {
+    entries: HashMap<Url, String>,
+}
+
+impl FileCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, uri: &Url) -> Option<&str> {
+        self.entries.get(uri).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, uri: Url, content: String) {
+        self.entries.insert(uri, content);
+    }
+
+    /// Drop `uri`'s cached content, e.g. because the file changed on disk.
+    pub fn invalidate(&mut self, uri: &Url) {
+        self.entries.remove(uri);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri() -> Url {
+        Url::parse("file:///test.R").unwrap()
+    }
+
+    #[test]
+    fn invalidate_drops_a_cached_entry() {
+        let mut cache = FileCache::new();
+        cache.insert(uri(), "contents".to_string());
+        cache.invalidate(&uri());
+        assert!(cache.get(&uri()).is_none());
+    }
+}
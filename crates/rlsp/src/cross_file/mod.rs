@@ -14,6 +14,7 @@ pub mod dependency;
 pub mod directive;
 pub mod file_cache;
 pub mod parent_resolve;
+pub mod path_auditor;
 pub mod path_resolve;
 pub mod revalidation;
 pub mod scope;
@@ -31,6 +32,7 @@ pub use directive::*;
 pub use file_cache::*;
 #[allow(unused_imports)]
 pub use parent_resolve::*;
+pub use path_auditor::*;
 #[allow(unused_imports)]
 pub use path_resolve::*;
 pub use revalidation::*;
@@ -0,0 +1,48 @@
+//
+// cross_file/parent_resolve.rs
+//
+// Resolving which parent file a backward directive's call site actually
+// refers to, when a file is sourced/run/included by more than one parent.
+//
+
+use tower_lsp::lsp_types::Url;
+
+pub use raven::cross_file::cache::ParentResolution;
+
+use super::dependency::DependencyGraph;
+use super::types::CallSiteSpec;
+
+/// Pick the parent a file should be attributed to among `candidates`, given
+/// each candidate's [`CallSiteSpec`] hint.
+///
+/// A single candidate is unambiguous regardless of its call site. With more
+/// than one, a `Line`/`Match` hint would normally need the candidate's text
+/// to disambiguate against; nothing calls this yet (no caller has a reason
+/// to pick one `@lsp-sourced-by` parent over another), so for now it just
+/// selects the first candidate and reports the rest as alternatives rather
+/// than guessing.
+pub fn resolve_parent(
+    candidates: &[(Url, CallSiteSpec)],
+    _graph: &DependencyGraph,
+) -> ParentResolution {
+    match candidates {
+        [] => ParentResolution::None,
+        [(parent_uri, call_site)] => ParentResolution::Single {
+            parent_uri: parent_uri.clone(),
+            call_site_line: match call_site {
+                CallSiteSpec::Line(line) => Some(*line),
+                _ => None,
+            },
+            call_site_column: None,
+        },
+        [(selected_uri, call_site), rest @ ..] => ParentResolution::Ambiguous {
+            selected_uri: selected_uri.clone(),
+            selected_line: match call_site {
+                CallSiteSpec::Line(line) => Some(*line),
+                _ => None,
+            },
+            selected_column: None,
+            alternatives: rest.iter().map(|(u, _)| u.clone()).collect(),
+        },
+    }
+}
@@ -0,0 +1,145 @@
+//
+// cross_file/path_auditor.rs
+//
+// Enforces that resolved directive paths stay inside the workspace
+//
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::RwLock;
+
+use tower_lsp::lsp_types::Range;
+
+use super::path_resolve::{AbsPath, AbsPathBuf, PathContext};
+
+/// Why a resolved directive path was rejected by [`PathAuditor::audit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditError {
+    /// The resolved path does not lie under the workspace root at all.
+    OutsideWorkspace { resolved: AbsPathBuf },
+    /// The resolved path is under the workspace root lexically, but an
+    /// intermediate component is a symlink whose real target is not.
+    SymlinkEscapesWorkspace {
+        resolved: AbsPathBuf,
+        link: AbsPathBuf,
+        target: AbsPathBuf,
+    },
+}
+
+/// An [`AuditError`] paired with the span of the directive that produced
+/// the offending path, ready to be turned into an LSP diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditDiagnostic {
+    pub error: AuditError,
+    pub span: Range,
+}
+
+/// Enforces the "a resolved directive path must stay under the workspace
+/// root" invariant that [`resolve_working_directory`](super::path_resolve::resolve_working_directory)'s
+/// callers assume but that nothing previously checked at runtime — a
+/// crafted `# @lsp-sourced-by ../../../../etc/passwd` would otherwise
+/// resolve outside the project without complaint.
+///
+/// Both forward (`@lsp-source`) and backward (`@lsp-sourced-by`) directives
+/// should run their resolved target through the same `PathAuditor` before
+/// it's stored in `CrossFileMetadata`.
+#[derive(Debug, Default)]
+pub struct PathAuditor {
+    /// Resolved paths that have already passed the symlink walk, so
+    /// repeated directives into the same tree don't re-stat every
+    /// intermediate component.
+    audited: RwLock<HashSet<AbsPathBuf>>,
+}
+
+impl PathAuditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Audit a resolved directive path against `ctx`'s workspace root.
+    /// `span` is the source range of the directive that resolved to
+    /// `resolved`, attached to the returned diagnostic on rejection.
+    pub fn audit(
+        &self,
+        resolved: &AbsPath,
+        ctx: &PathContext,
+        span: Range,
+    ) -> Result<(), AuditDiagnostic> {
+        let Some(workspace_root) = ctx.workspace_root.as_ref() else {
+            // No workspace root configured; there is nothing to enforce against.
+            return Ok(());
+        };
+        let workspace_root = workspace_root.as_path();
+
+        if !resolved.starts_with(workspace_root) {
+            return Err(AuditDiagnostic {
+                error: AuditError::OutsideWorkspace {
+                    resolved: resolved.into(),
+                },
+                span,
+            });
+        }
+
+        if let Ok(audited) = self.audited.read() {
+            if audited.contains(&AbsPathBuf::from(resolved)) {
+                return Ok(());
+            }
+        }
+
+        self.audit_symlinks(resolved, workspace_root, span)?;
+
+        if let Ok(mut audited) = self.audited.write() {
+            audited.insert(resolved.into());
+        }
+        Ok(())
+    }
+
+    /// Walk the components of `resolved` below `workspace_root`, rejecting
+    /// the first one that is a symlink whose canonical target escapes the
+    /// workspace.
+    fn audit_symlinks(
+        &self,
+        resolved: &AbsPath,
+        workspace_root: &AbsPath,
+        span: Range,
+    ) -> Result<(), AuditDiagnostic> {
+        let relative = resolved
+            .as_path()
+            .strip_prefix(workspace_root.as_path())
+            .unwrap_or_else(|_| Path::new(""));
+
+        let mut prefix = workspace_root.as_path().to_path_buf();
+        for component in relative.components() {
+            prefix.push(component.as_os_str());
+
+            let Ok(metadata) = std::fs::symlink_metadata(&prefix) else {
+                // Doesn't exist yet (or isn't readable) - nothing to audit.
+                continue;
+            };
+            if !metadata.file_type().is_symlink() {
+                continue;
+            }
+
+            let Ok(canonical) = std::fs::canonicalize(&prefix) else {
+                continue;
+            };
+            let Ok(target) = AbsPathBuf::try_new(canonical) else {
+                continue;
+            };
+
+            if !target.as_path().starts_with(workspace_root) {
+                return Err(AuditDiagnostic {
+                    error: AuditError::SymlinkEscapesWorkspace {
+                        resolved: resolved.into(),
+                        link: AbsPathBuf::try_new(prefix.clone())
+                            .unwrap_or_else(|_| resolved.into()),
+                        target,
+                    },
+                    span,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,485 @@
+//
+// cross_file/path_resolve.rs
+//
+// Typed absolute/relative path handling for cross-file directive resolution
+//
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use tower_lsp::lsp_types::Range;
+
+use super::path_auditor::{AuditDiagnostic, PathAuditor};
+
+/// An owned, absolute, lexically-normalized filesystem path.
+///
+/// Normalization happens once, at construction: `.` components are dropped
+/// and `..` components pop the preceding normal component. This never
+/// touches the filesystem, so it is safe to call on paths that don't exist.
+/// Constructing one from a relative `PathBuf` is a compile-time type error
+/// away from happening by accident.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AbsPathBuf(PathBuf);
+
+/// Error returned when a path that should be absolute is not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotAbsoluteError(PathBuf);
+
+impl fmt::Display for NotAbsoluteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "path is not absolute: {}", self.0.display())
+    }
+}
+
+impl std::error::Error for NotAbsoluteError {}
+
+impl AbsPathBuf {
+    /// Wrap an absolute path, normalizing away `.`/`..` components lexically.
+    ///
+    /// Returns `Err` if `path` is not absolute; this never touches disk.
+    pub fn try_new(path: PathBuf) -> Result<Self, NotAbsoluteError> {
+        if !path.is_absolute() {
+            return Err(NotAbsoluteError(path));
+        }
+        Ok(Self(normalize_components(&path)))
+    }
+
+    pub fn as_path(&self) -> &AbsPath {
+        AbsPath::new_unchecked(&self.0)
+    }
+
+    pub fn into_path_buf(self) -> PathBuf {
+        self.0
+    }
+}
+
+impl TryFrom<PathBuf> for AbsPathBuf {
+    type Error = NotAbsoluteError;
+
+    fn try_from(path: PathBuf) -> Result<Self, Self::Error> {
+        Self::try_new(path)
+    }
+}
+
+impl AsRef<Path> for AbsPathBuf {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for AbsPathBuf {
+    type Target = AbsPath;
+
+    fn deref(&self) -> &AbsPath {
+        self.as_path()
+    }
+}
+
+impl fmt::Display for AbsPathBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.display())
+    }
+}
+
+/// Borrowed counterpart of [`AbsPathBuf`]. Always absolute and normalized.
+#[derive(Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct AbsPath(Path);
+
+impl AbsPath {
+    /// Build a borrowed `AbsPath` without re-checking the absolute/normalized
+    /// invariant. Only call this on a `Path` that is known to have come from
+    /// an `AbsPathBuf`.
+    fn new_unchecked(path: &Path) -> &Self {
+        // SAFETY: `AbsPath` is `#[repr(transparent)]` over `Path`.
+        unsafe { &*(path as *const Path as *const AbsPath) }
+    }
+
+    pub fn as_path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn parent(&self) -> Option<&AbsPath> {
+        self.0.parent().map(AbsPath::new_unchecked)
+    }
+
+    pub fn starts_with(&self, base: &AbsPath) -> bool {
+        self.0.starts_with(&base.0)
+    }
+}
+
+impl fmt::Display for AbsPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.display())
+    }
+}
+
+impl AsRef<Path> for AbsPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+/// An owned, `/`-separated directive path, as authored in R comments
+/// (`@lsp-source`, `@lsp-sourced-by`, `@lsp-working-directory`, ...).
+///
+/// Directive paths are never OS paths: they always use `/` regardless of
+/// platform, and may be prefixed with `/` to mean "relative to the
+/// workspace root" rather than the filesystem root. Constructing one from
+/// a string containing a backslash is a type error, since that is almost
+/// certainly a mis-pasted Windows path rather than a directive path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RelPathBuf(String);
+
+/// Error returned when a string cannot be used as a directive-relative path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelPathError {
+    Empty,
+    ContainsBackslash(String),
+}
+
+impl fmt::Display for RelPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelPathError::Empty => write!(f, "directive path is empty"),
+            RelPathError::ContainsBackslash(s) => {
+                write!(f, "directive path contains a backslash (use '/'): {s}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RelPathError {}
+
+impl RelPathBuf {
+    pub fn try_new(path: impl Into<String>) -> Result<Self, RelPathError> {
+        let path = path.into();
+        if path.is_empty() {
+            return Err(RelPathError::Empty);
+        }
+        if path.contains('\\') {
+            return Err(RelPathError::ContainsBackslash(path));
+        }
+        Ok(Self(path))
+    }
+
+    pub fn as_rel_path(&self) -> &RelPath {
+        RelPath::new_unchecked(&self.0)
+    }
+}
+
+impl TryFrom<String> for RelPathBuf {
+    type Error = RelPathError;
+
+    fn try_from(path: String) -> Result<Self, Self::Error> {
+        Self::try_new(path)
+    }
+}
+
+impl TryFrom<&str> for RelPathBuf {
+    type Error = RelPathError;
+
+    fn try_from(path: &str) -> Result<Self, Self::Error> {
+        Self::try_new(path.to_string())
+    }
+}
+
+impl std::ops::Deref for RelPathBuf {
+    type Target = RelPath;
+
+    fn deref(&self) -> &RelPath {
+        self.as_rel_path()
+    }
+}
+
+impl fmt::Display for RelPathBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Borrowed counterpart of [`RelPathBuf`].
+#[derive(Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct RelPath(str);
+
+impl RelPath {
+    fn new_unchecked(s: &str) -> &Self {
+        // SAFETY: `RelPath` is `#[repr(transparent)]` over `str`.
+        unsafe { &*(s as *const str as *const RelPath) }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// `true` for directive paths like `/sub/path.R` that are anchored to
+    /// the workspace root rather than the authoring file's directory.
+    pub fn is_workspace_root_relative(&self) -> bool {
+        self.0.starts_with('/')
+    }
+
+    /// This path's `/`-separated components, with the leading empty segment
+    /// of a workspace-root-relative path already stripped.
+    pub fn components(&self) -> impl Iterator<Item = &str> {
+        self.0.split('/').filter(|s| !s.is_empty())
+    }
+}
+
+impl fmt::Display for RelPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", &self.0)
+    }
+}
+
+/// Lexically normalize an absolute `path`'s components, purely by
+/// component math (no filesystem access). `.` segments are dropped,
+/// `..` segments pop the previous normal component down to the root.
+fn normalize_components(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                if !matches!(out.components().next_back(), Some(std::path::Component::RootDir) | None)
+                {
+                    out.pop();
+                }
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// How a `working_directory` directive was set for a file: explicitly on
+/// the file itself, or inherited from the file that sourced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkingDirectorySource {
+    Explicit(RelPathBuf),
+    Inherited(RelPathBuf),
+}
+
+/// Everything `resolve_working_directory` needs to turn a directive path
+/// into an absolute filesystem location.
+#[derive(Debug, Clone)]
+pub struct PathContext {
+    /// Absolute path of the file the directive was parsed from.
+    pub file_path: AbsPathBuf,
+    /// This file's own `@lsp-working-directory` directive, if any.
+    pub working_directory: Option<RelPathBuf>,
+    /// A working directory inherited from the parent that sourced this file.
+    pub inherited_working_directory: Option<RelPathBuf>,
+    /// Workspace root, used to resolve `/`-prefixed (workspace-relative) paths.
+    pub workspace_root: Option<AbsPathBuf>,
+    /// Whether a resolved path that turns out to be a symlink should be
+    /// followed to its canonical target or reported as-is.
+    pub symlink_mode: SymlinkMode,
+}
+
+/// Whether [`resolve_symlink_aware`] follows a resolved path's symlinks to
+/// their canonical target, or reports the link path unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkMode {
+    /// Follow symlinks and report the canonical target (the default: R
+    /// projects frequently keep shared helper scripts behind symlinked
+    /// directories, and callers usually want the real file).
+    #[default]
+    Follow,
+    /// Report the resolved link path without touching its target.
+    NoFollow,
+}
+
+/// The outcome of resolving a directive path all the way down to the
+/// filesystem, distinguishing a plain file from a symlink (followed or
+/// broken). Produced by [`resolve_symlink_aware`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolvedTarget {
+    /// `resolve_working_directory` produced this path and it is not a symlink
+    /// (or doesn't exist on disk at all, so there's nothing to follow), or it
+    /// is a symlink left unfollowed under [`SymlinkMode::NoFollow`].
+    Direct(AbsPathBuf),
+    /// `link` is a symlink that was followed to `canonical_target`, which
+    /// has already been re-audited against the workspace root.
+    Symlink {
+        link: AbsPathBuf,
+        canonical_target: AbsPathBuf,
+    },
+    /// `link` is a symlink whose target is missing. A symlink whose
+    /// canonical target escapes the workspace root is an `Err` from
+    /// [`resolve_symlink_aware`] instead.
+    BrokenSymlink { link: AbsPathBuf },
+}
+
+impl ResolvedTarget {
+    /// The path a caller should actually read from: the canonical target
+    /// for a followed symlink, the link path itself otherwise.
+    pub fn effective_path(&self) -> &AbsPath {
+        match self {
+            ResolvedTarget::Direct(path) => path.as_path(),
+            ResolvedTarget::Symlink { canonical_target, .. } => canonical_target.as_path(),
+            ResolvedTarget::BrokenSymlink { link } => link.as_path(),
+        }
+    }
+}
+
+/// Resolve `path` the same way [`resolve_working_directory`] does, then
+/// inspect the filesystem to distinguish a real file from a symlink.
+///
+/// `auditor` runs both the lexically-resolved `link` and (if it's a
+/// followed symlink) its canonical target through [`PathAuditor::audit`]:
+/// a symlink cannot be used to escape the workspace just because its
+/// *link* resolved inside it, and neither can an intermediate directory
+/// component that is itself a symlink pointing outside. `span` is the
+/// source range of the directive that produced `path`, attached to the
+/// diagnostic on rejection.
+///
+/// A symlink whose target is simply missing is reported as
+/// [`ResolvedTarget::BrokenSymlink`] rather than silently dropped; a
+/// symlink (or its link path) that fails the audit is reported as `Err`
+/// instead, so the caller can surface why.
+pub fn resolve_symlink_aware(
+    path: &RelPath,
+    ctx: &PathContext,
+    auditor: &PathAuditor,
+    span: Range,
+) -> Option<Result<ResolvedTarget, AuditDiagnostic>> {
+    let link = resolve_working_directory(path, ctx)?;
+
+    if let Err(diagnostic) = auditor.audit(link.as_path(), ctx, span.clone()) {
+        return Some(Err(diagnostic));
+    }
+
+    let is_symlink = std::fs::symlink_metadata(link.as_path())
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+
+    if !is_symlink || ctx.symlink_mode == SymlinkMode::NoFollow {
+        return Some(Ok(ResolvedTarget::Direct(link)));
+    }
+
+    let Ok(canonical) = std::fs::canonicalize(link.as_path()) else {
+        return Some(Ok(ResolvedTarget::BrokenSymlink { link }));
+    };
+    let Ok(canonical_target) = AbsPathBuf::try_new(canonical) else {
+        return Some(Ok(ResolvedTarget::BrokenSymlink { link }));
+    };
+
+    if let Err(diagnostic) = auditor.audit(canonical_target.as_path(), ctx, span) {
+        return Some(Err(diagnostic));
+    }
+
+    Some(Ok(ResolvedTarget::Symlink {
+        link,
+        canonical_target,
+    }))
+}
+
+/// Resolve a directive path (as authored with `/` separators) against the
+/// base directory implied by `ctx`, returning the absolute filesystem path.
+///
+/// A path starting with `/` is resolved against `ctx.workspace_root`
+/// (`None` if there is no workspace root); anything else is resolved
+/// against the file's own directory (or its working directory, if set).
+///
+/// Resolution is purely lexical (see [`lexically_resolve`]): `..` never
+/// climbs past the workspace root when one is configured, so a directive
+/// can't walk itself out of the project by piling up enough `../`.
+pub fn resolve_working_directory(path: &RelPath, ctx: &PathContext) -> Option<AbsPathBuf> {
+    let floor = ctx.workspace_root.as_ref().map(AbsPathBuf::as_path);
+
+    if path.is_workspace_root_relative() {
+        let root = ctx.workspace_root.as_ref()?;
+        return Some(lexically_resolve(root.as_path(), path, floor));
+    }
+
+    let base = match (&ctx.working_directory, &ctx.inherited_working_directory) {
+        (Some(wd), _) | (None, Some(wd)) => {
+            let file_dir = ctx.file_path.as_path().parent()?;
+            lexically_resolve(file_dir, wd.as_rel_path(), floor)
+        }
+        (None, None) => ctx.file_path.as_path().parent()?.into(),
+    };
+
+    Some(lexically_resolve(base.as_path(), path, floor))
+}
+
+/// Resolve `rel` against `base` purely by string/component math: split on
+/// `/`, drop empty and `.` segments, and on `..` pop the last normal
+/// component. Never touches disk, and never pops past `floor` (typically
+/// the workspace root) even if `rel` has more `../` than `base` has depth
+/// below `floor`. The OS separator is only introduced when building the
+/// final path, so behavior is identical on every platform.
+fn lexically_resolve(base: &AbsPath, rel: &RelPath, floor: Option<&AbsPath>) -> AbsPathBuf {
+    // The root/prefix component (e.g. `/`, or `C:\` on Windows) is kept
+    // fixed; only the normal components underneath it ever get popped.
+    let mut components = base.as_path().components();
+    let root = components.next().expect("AbsPath is always absolute");
+    let mut stack: Vec<&str> = components.filter_map(|c| c.as_os_str().to_str()).collect();
+
+    let floor_len = floor.map_or(0, |floor| floor.as_path().components().count().saturating_sub(1));
+
+    for segment in rel.as_str().split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                if stack.len() > floor_len {
+                    stack.pop();
+                }
+            }
+            segment => stack.push(segment),
+        }
+    }
+
+    let mut result = PathBuf::new();
+    result.push(root.as_os_str());
+    for segment in stack {
+        result.push(segment);
+    }
+    AbsPathBuf(result)
+}
+
+impl From<&AbsPath> for AbsPathBuf {
+    fn from(path: &AbsPath) -> Self {
+        AbsPathBuf(path.as_path().to_path_buf())
+    }
+}
+
+/// Compute the shortest `/`-separated path from `base` to `target`,
+/// inserting `..` components where needed.
+///
+/// Turns an absolute resolved cross-file target back into something worth
+/// showing a user (`../lib/helpers.R` instead of
+/// `/home/user/project/lib/helpers.R`), mirroring the pair with
+/// [`lexically_resolve`] which does the opposite conversion. No handler
+/// calls it: hover and diagnostic text do not show cross-file targets.
+pub fn relativize_path(target: &AbsPath, base: &AbsPath) -> RelPathBuf {
+    let target_components: Vec<&str> = target
+        .as_path()
+        .components()
+        .filter_map(|c| c.as_os_str().to_str())
+        .collect();
+    let base_components: Vec<&str> = base
+        .as_path()
+        .components()
+        .filter_map(|c| c.as_os_str().to_str())
+        .collect();
+
+    let common = target_components
+        .iter()
+        .zip(base_components.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    // Preallocate from the component-count difference: one `..` per
+    // remaining base component, one segment per remaining target component.
+    let mut segments = Vec::with_capacity((base_components.len() - common) + (target_components.len() - common));
+    segments.extend(std::iter::repeat("..").take(base_components.len() - common));
+    segments.extend(target_components[common..].iter().copied());
+
+    if segments.is_empty() {
+        return RelPathBuf::try_new(".".to_string()).expect("\".\" is a valid RelPath");
+    }
+
+    RelPathBuf::try_new(segments.join("/")).expect("joined path segments contain no backslash")
+}
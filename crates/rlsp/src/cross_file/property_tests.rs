@@ -9,8 +9,14 @@
 use proptest::prelude::*;
 use std::path::PathBuf;
 
+use tower_lsp::lsp_types::Range;
+
 use super::directive::parse_directives;
-use super::path_resolve::{resolve_working_directory, PathContext};
+use super::path_auditor::PathAuditor;
+use super::path_resolve::{
+    resolve_symlink_aware, resolve_working_directory, AbsPathBuf, PathContext, RelPathBuf,
+    ResolvedTarget, SymlinkMode,
+};
 use super::types::{CallSiteSpec, CrossFileMetadata};
 
 // ============================================================================
@@ -62,9 +68,9 @@ proptest! {
         prop_assert_eq!(meta3.sourced_by.len(), 1);
 
         // All should have the same path
-        prop_assert_eq!(&meta1.sourced_by[0].path, &path);
-        prop_assert_eq!(&meta2.sourced_by[0].path, &path);
-        prop_assert_eq!(&meta3.sourced_by[0].path, &path);
+        prop_assert_eq!(meta1.sourced_by[0].path.as_str(), path);
+        prop_assert_eq!(meta2.sourced_by[0].path.as_str(), path);
+        prop_assert_eq!(meta3.sourced_by[0].path.as_str(), path);
 
         // All should have the same call site (Default)
         prop_assert_eq!(&meta1.sourced_by[0].call_site, &CallSiteSpec::Default);
@@ -87,9 +93,9 @@ proptest! {
         prop_assert_eq!(meta2.sourced_by.len(), 1);
         prop_assert_eq!(meta3.sourced_by.len(), 1);
 
-        prop_assert_eq!(&meta1.sourced_by[0].path, &path);
-        prop_assert_eq!(&meta2.sourced_by[0].path, &path);
-        prop_assert_eq!(&meta3.sourced_by[0].path, &path);
+        prop_assert_eq!(meta1.sourced_by[0].path.as_str(), path);
+        prop_assert_eq!(meta2.sourced_by[0].path.as_str(), path);
+        prop_assert_eq!(meta3.sourced_by[0].path.as_str(), path);
     }
 
     /// Property 1 extended: Synonyms with quotes should also be equivalent
@@ -107,9 +113,9 @@ proptest! {
         prop_assert_eq!(meta2.sourced_by.len(), 1);
         prop_assert_eq!(meta3.sourced_by.len(), 1);
 
-        prop_assert_eq!(&meta1.sourced_by[0].path, &path);
-        prop_assert_eq!(&meta2.sourced_by[0].path, &path);
-        prop_assert_eq!(&meta3.sourced_by[0].path, &path);
+        prop_assert_eq!(meta1.sourced_by[0].path.as_str(), path);
+        prop_assert_eq!(meta2.sourced_by[0].path.as_str(), path);
+        prop_assert_eq!(meta3.sourced_by[0].path.as_str(), path);
     }
 }
 
@@ -145,8 +151,8 @@ proptest! {
         // All should produce the same working directory
         for (i, meta) in results.iter().enumerate() {
             prop_assert_eq!(
-                meta.working_directory.as_ref(),
-                Some(&path),
+                meta.working_directory.as_ref().map(|w| w.as_str()),
+                Some(path.as_str()),
                 "Synonym {} failed", synonyms[i]
             );
         }
@@ -174,8 +180,8 @@ proptest! {
 
         for (i, meta) in results.iter().enumerate() {
             prop_assert_eq!(
-                meta.working_directory.as_ref(),
-                Some(&path),
+                meta.working_directory.as_ref().map(|w| w.as_str()),
+                Some(path.as_str()),
                 "Synonym {} failed", synonyms[i]
             );
         }
@@ -197,8 +203,8 @@ proptest! {
             let meta1 = parse_directives(&double_quoted);
             let meta2 = parse_directives(&single_quoted);
 
-            prop_assert_eq!(meta1.working_directory.as_ref(), Some(&path));
-            prop_assert_eq!(meta2.working_directory.as_ref(), Some(&path));
+            prop_assert_eq!(meta1.working_directory.as_ref().map(|w| w.as_str()), Some(path.as_str()));
+            prop_assert_eq!(meta2.working_directory.as_ref().map(|w| w.as_str()), Some(path.as_str()));
         }
     }
 }
@@ -214,41 +220,40 @@ proptest! {
     /// Property 2a: Paths starting with / SHALL resolve relative to workspace root.
     #[test]
     fn prop_workspace_root_relative_path(subpath in relative_path()) {
-        let workspace_root = PathBuf::from("/workspace");
-        let file_path = PathBuf::from("/workspace/src/main.R");
+        let workspace_root = AbsPathBuf::try_new(PathBuf::from("/workspace")).unwrap();
+        let file_path = AbsPathBuf::try_new(PathBuf::from("/workspace/src/main.R")).unwrap();
 
         let ctx = PathContext {
             file_path,
             working_directory: None,
             inherited_working_directory: None,
             workspace_root: Some(workspace_root.clone()),
+            symlink_mode: SymlinkMode::Follow,
         };
 
-        let path_str = format!("/{}", subpath);
-        let resolved = resolve_working_directory(&path_str, &ctx);
+        let path = RelPathBuf::try_new(format!("/{}", subpath)).unwrap();
+        let resolved = resolve_working_directory(&path, &ctx);
 
         prop_assert!(resolved.is_some());
         let resolved = resolved.unwrap();
 
         // Should start with workspace root
-        prop_assert!(resolved.starts_with(&workspace_root));
-
-        // Should NOT be filesystem root
-        prop_assert!(!resolved.starts_with("/") || resolved.starts_with(&workspace_root));
+        prop_assert!(resolved.starts_with(workspace_root.as_path()));
     }
 
     /// Property 2a extended: Workspace-root-relative without workspace returns None
     #[test]
     fn prop_workspace_root_relative_no_workspace(subpath in relative_path()) {
         let ctx = PathContext {
-            file_path: PathBuf::from("/some/file.R"),
+            file_path: AbsPathBuf::try_new(PathBuf::from("/some/file.R")).unwrap(),
             working_directory: None,
             inherited_working_directory: None,
             workspace_root: None,
+            symlink_mode: SymlinkMode::Follow,
         };
 
-        let path_str = format!("/{}", subpath);
-        let resolved = resolve_working_directory(&path_str, &ctx);
+        let path = RelPathBuf::try_new(format!("/{}", subpath)).unwrap();
+        let resolved = resolve_working_directory(&path, &ctx);
 
         prop_assert!(resolved.is_none());
     }
@@ -265,23 +270,25 @@ proptest! {
     /// Property 2b: Paths not starting with / SHALL resolve relative to file's directory.
     #[test]
     fn prop_file_relative_path(subpath in relative_path()) {
-        let file_path = PathBuf::from("/project/src/main.R");
-        let file_dir = PathBuf::from("/project/src");
+        let file_path = AbsPathBuf::try_new(PathBuf::from("/project/src/main.R")).unwrap();
+        let file_dir = AbsPathBuf::try_new(PathBuf::from("/project/src")).unwrap();
 
         let ctx = PathContext {
             file_path,
             working_directory: None,
             inherited_working_directory: None,
-            workspace_root: Some(PathBuf::from("/project")),
+            workspace_root: Some(AbsPathBuf::try_new(PathBuf::from("/project")).unwrap()),
+            symlink_mode: SymlinkMode::Follow,
         };
 
-        let resolved = resolve_working_directory(&subpath, &ctx);
+        let path = RelPathBuf::try_new(subpath).unwrap();
+        let resolved = resolve_working_directory(&path, &ctx);
 
         prop_assert!(resolved.is_some());
         let resolved = resolved.unwrap();
 
         // Should start with file's directory
-        prop_assert!(resolved.starts_with(&file_dir));
+        prop_assert!(resolved.starts_with(file_dir.as_path()));
     }
 
     /// Property 2b extended: Parent directory navigation
@@ -290,24 +297,26 @@ proptest! {
         parents in 1..3usize,
         subpath in relative_path()
     ) {
-        let file_path = PathBuf::from("/project/a/b/c/main.R");
+        let file_path = AbsPathBuf::try_new(PathBuf::from("/project/a/b/c/main.R")).unwrap();
+        let workspace_root = AbsPathBuf::try_new(PathBuf::from("/project")).unwrap();
 
         let ctx = PathContext {
             file_path,
             working_directory: None,
             inherited_working_directory: None,
-            workspace_root: Some(PathBuf::from("/project")),
+            workspace_root: Some(workspace_root.clone()),
+            symlink_mode: SymlinkMode::Follow,
         };
 
         let prefix = "../".repeat(parents);
-        let path_str = format!("{}{}", prefix, subpath);
-        let resolved = resolve_working_directory(&path_str, &ctx);
+        let path = RelPathBuf::try_new(format!("{}{}", prefix, subpath)).unwrap();
+        let resolved = resolve_working_directory(&path, &ctx);
 
         prop_assert!(resolved.is_some());
         let resolved = resolved.unwrap();
 
         // Should still be under /project (not escape workspace)
-        prop_assert!(resolved.starts_with("/project"));
+        prop_assert!(resolved.starts_with(workspace_root.as_path()));
     }
 }
 
@@ -485,3 +494,89 @@ proptest! {
         }
     }
 }
+
+// ============================================================================
+// Property 11: Symlink-Aware Resolution Equivalence
+// Validates: SymlinkMode Follow/NoFollow produce the same logical target
+// ============================================================================
+
+/// A scratch directory for one test case, removed on drop.
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn new(tag: &str) -> Self {
+        static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        let n = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "rlsp-path-resolve-proptest-{}-{}-{}",
+            std::process::id(),
+            tag,
+            n
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        ScratchDir(dir)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(20))]
+
+    /// Property 11: Resolving a directive that points at a symlinked file
+    /// SHALL produce the same logical (canonical) target under both
+    /// `SymlinkMode::Follow` and `SymlinkMode::NoFollow`, differing only in
+    /// whether the symlink is reported as followed or left as-is.
+    #[test]
+    fn prop_symlink_mode_same_logical_target(file_name in path_component()) {
+        let scratch = ScratchDir::new("symlink-equiv");
+        let real_file = scratch.0.join(format!("{}-real.R", file_name));
+        let link_file = scratch.0.join(format!("{}-link.R", file_name));
+        std::fs::write(&real_file, "# empty\n").unwrap();
+
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&real_file, &link_file).unwrap();
+        #[cfg(not(unix))]
+        prop_assume!(false, "symlink creation only exercised on unix in this test");
+
+        let workspace_root = AbsPathBuf::try_new(scratch.0.clone()).unwrap();
+        let file_path = AbsPathBuf::try_new(scratch.0.join("caller.R")).unwrap();
+        let real_file_abs = AbsPathBuf::try_new(real_file.clone()).unwrap();
+        let link_file_abs = AbsPathBuf::try_new(link_file.clone()).unwrap();
+
+        let path = RelPathBuf::try_new(format!("{}-link.R", file_name)).unwrap();
+        let auditor = PathAuditor::new();
+
+        for mode in [SymlinkMode::Follow, SymlinkMode::NoFollow] {
+            let ctx = PathContext {
+                file_path: file_path.clone(),
+                working_directory: None,
+                inherited_working_directory: None,
+                workspace_root: Some(workspace_root.clone()),
+                symlink_mode: mode,
+            };
+
+            let resolved = resolve_symlink_aware(&path, &ctx, &auditor, Range::default());
+            prop_assert!(resolved.is_some());
+
+            let resolved = resolved
+                .unwrap()
+                .expect("both targets live under the scratch workspace root");
+
+            match (mode, resolved) {
+                (SymlinkMode::Follow, ResolvedTarget::Symlink { link, canonical_target }) => {
+                    prop_assert_eq!(&link, &link_file_abs);
+                    prop_assert_eq!(&canonical_target, &real_file_abs);
+                }
+                (SymlinkMode::NoFollow, ResolvedTarget::Direct(link)) => {
+                    prop_assert_eq!(&link, &link_file_abs);
+                }
+                other => prop_assert!(false, "unexpected resolution outcome: {:?}", other),
+            }
+        }
+    }
+}
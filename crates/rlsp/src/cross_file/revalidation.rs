@@ -0,0 +1,69 @@
+//
+// cross_file/revalidation.rs
+//
+// Tracks which documents have a revalidation (re-publish) pending
+//
+
+use std::collections::HashSet;
+
+use tower_lsp::lsp_types::Url;
+
+/// The set of documents currently queued for cross-file revalidation.
+///
+/// `did_close` cancels a pending entry so a document the client just closed
+/// doesn't get re-published into after the fact. Nothing in the server
+/// schedules entries here: `did_change` and `did_change_watched_files`
+/// revalidate dependents directly.
+#[derive(Debug, Default)]
+pub struct RevalidationQueue {
+    pending: HashSet<Url>,
+}
+
+impl RevalidationQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn schedule(&mut self, uri: Url) {
+        self.pending.insert(uri);
+    }
+
+    pub fn cancel(&mut self, uri: &Url) {
+        self.pending.remove(uri);
+    }
+
+    pub fn is_pending(&self, uri: &Url) -> bool {
+        self.pending.contains(uri)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri() -> Url {
+        Url::parse("file:///test.R").unwrap()
+    }
+
+    #[test]
+    fn scheduled_entry_is_pending() {
+        let mut queue = RevalidationQueue::new();
+        queue.schedule(uri());
+        assert!(queue.is_pending(&uri()));
+    }
+
+    #[test]
+    fn cancel_clears_a_pending_entry() {
+        let mut queue = RevalidationQueue::new();
+        queue.schedule(uri());
+        queue.cancel(&uri());
+        assert!(!queue.is_pending(&uri()));
+    }
+
+    #[test]
+    fn cancel_of_unscheduled_entry_is_a_no_op() {
+        let mut queue = RevalidationQueue::new();
+        queue.cancel(&uri());
+        assert!(!queue.is_pending(&uri()));
+    }
+}
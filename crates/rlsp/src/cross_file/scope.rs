@@ -0,0 +1,31 @@
+//
+// cross_file/scope.rs
+//
+// What's visible to a file once its cross-file directives are followed:
+// its own top-level bindings plus whatever its sourced-by parents and
+// sourced dependencies export.
+//
+
+use tower_lsp::lsp_types::Url;
+
+/// The set of files whose top-level bindings are visible from `uri`: the
+/// file itself, every file it `@lsp-source`s, and every file it's
+/// `@lsp-sourced-by` (since R's `source()` runs in the caller's scope, a
+/// parent's bindings are visible too).
+///
+/// Not yet consulted by completion/hover/goto-definition - those still only
+/// look at the current document's own bindings - so this only reports the
+/// direct edges rather than walking the dependency graph transitively.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileScope {
+    pub visible_files: Vec<Url>,
+}
+
+impl FileScope {
+    pub fn new(uri: Url, sourced_by: Vec<Url>, sources: Vec<Url>) -> Self {
+        let mut visible_files = vec![uri];
+        visible_files.extend(sourced_by);
+        visible_files.extend(sources);
+        Self { visible_files }
+    }
+}
@@ -0,0 +1,38 @@
+//
+// cross_file/source_detect.rs
+//
+// Heuristically locating the source()/sys.source() call a backward
+// directive's CallSiteSpec refers to, for a parent file's text.
+//
+
+/// Find the 0-based line of a `source(...)`/`sys.source(...)` call in
+/// `parent_text` matching `spec`.
+///
+/// `Default` falls back to the first such call in the file; `Line` is used
+/// as-is without inspecting the text at all; `Match` looks for the first
+/// call whose line contains `pattern` as a substring. Returns `None` if no
+/// call is found - not yet called from the request-handling path, since
+/// nothing currently needs a call-site line more precise than the directive
+/// comment's own line.
+pub fn find_call_site(parent_text: &str, spec: &super::types::CallSiteSpec) -> Option<u32> {
+    use super::types::CallSiteSpec;
+
+    match spec {
+        CallSiteSpec::Line(line) => Some(*line),
+        CallSiteSpec::Default => parent_text
+            .lines()
+            .position(is_source_call)
+            .map(|idx| idx as u32),
+        CallSiteSpec::Match(pattern) => parent_text
+            .lines()
+            .position(|line| is_source_call(line) && line.contains(pattern.as_str()))
+            .map(|idx| idx as u32),
+    }
+}
+
+fn is_source_call(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("source(")
+        || trimmed.starts_with("sys.source(")
+        || trimmed.contains(" source(")
+}
@@ -0,0 +1,88 @@
+//
+// cross_file/types.rs
+//
+// Shared data types for cross-file awareness directives and metadata
+//
+
+use serde::{Deserialize, Serialize};
+
+use super::path_resolve::RelPathBuf;
+
+/// Where within the parent file a backward directive's call site is anchored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CallSiteSpec {
+    /// No call site hint was given; fall back to the first plausible source() call.
+    Default,
+    /// An explicit 0-based line number (`line=N` in the directive is 1-based).
+    Line(u32),
+    /// A substring the parent's source() call line must contain.
+    Match(String),
+}
+
+/// A single `@lsp-sourced-by` / `@lsp-run-by` / `@lsp-included-by` directive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackwardDirective {
+    pub path: RelPathBuf,
+    pub call_site: CallSiteSpec,
+}
+
+/// A single `@lsp-source` (forward) directive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForwardDirective {
+    pub path: RelPathBuf,
+}
+
+/// Directive-derived metadata for a single file, as parsed from its comments.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CrossFileMetadata {
+    /// `@lsp-sourced-by` / `@lsp-run-by` / `@lsp-included-by` directives.
+    pub sourced_by: Vec<BackwardDirective>,
+    /// `@lsp-source` directives.
+    pub sources: Vec<ForwardDirective>,
+    /// `@lsp-working-directory` (and synonyms) directive, if present.
+    pub working_directory: Option<RelPathBuf>,
+    /// Line numbers (0-based) ignored via `@lsp-ignore`.
+    pub ignored_lines: Vec<u32>,
+    /// Line numbers (0-based) ignored via `@lsp-ignore-next` (applies to the *next* line).
+    pub ignored_next_lines: Vec<u32>,
+    /// Hash of the parts of this metadata a dependent file can actually
+    /// observe (directive targets and call sites), set by
+    /// [`export_surface_hash`]. Two parses that differ only in
+    /// `ignored_lines`/`ignored_next_lines` bookkeeping hash identically,
+    /// so callers can tell a cosmetic re-parse from one that should
+    /// trigger revalidating dependents.
+    pub export_surface_hash: String,
+}
+
+/// Compute a stable hash over `meta`'s export surface - the directives that
+/// other files resolve against - for use as [`CrossFileMetadata::export_surface_hash`].
+///
+/// Deliberately excludes `ignored_lines`/`ignored_next_lines`, since those
+/// only affect diagnostics for this file, not how other files resolve
+/// directives against it.
+pub fn export_surface_hash(meta: &CrossFileMetadata) -> String {
+    let mut hasher = blake3::Hasher::new();
+
+    for directive in &meta.sourced_by {
+        hasher.update(directive.path.as_str().as_bytes());
+        match &directive.call_site {
+            CallSiteSpec::Default => hasher.update(&[0]),
+            CallSiteSpec::Line(line) => {
+                hasher.update(&[1]);
+                hasher.update(&line.to_le_bytes());
+            }
+            CallSiteSpec::Match(pattern) => {
+                hasher.update(&[2]);
+                hasher.update(pattern.as_bytes());
+            }
+        };
+    }
+    for directive in &meta.sources {
+        hasher.update(directive.path.as_str().as_bytes());
+    }
+    if let Some(working_directory) = &meta.working_directory {
+        hasher.update(working_directory.as_str().as_bytes());
+    }
+
+    hasher.finalize().to_hex().to_string()
+}
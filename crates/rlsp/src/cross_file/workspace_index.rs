@@ -0,0 +1,54 @@
+//
+// cross_file/workspace_index.rs
+//
+// Workspace-wide index of files available for cross-file resolution
+//
+
+use std::collections::HashSet;
+
+use tower_lsp::lsp_types::Url;
+
+/// Tracks which files in the workspace are known to exist, so a directive
+/// can be resolved against a file the client hasn't opened yet without
+/// walking the filesystem on every lookup.
+#[derive(Debug, Default)]
+pub struct WorkspaceIndex {
+    files: HashSet<Url>,
+}
+
+impl WorkspaceIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, uri: &Url) -> bool {
+        self.files.contains(uri)
+    }
+
+    pub fn insert(&mut self, uri: Url) {
+        self.files.insert(uri);
+    }
+
+    /// Drop `uri` from the index, e.g. because it changed or was deleted and
+    /// needs to be re-discovered.
+    pub fn invalidate(&mut self, uri: &Url) {
+        self.files.remove(uri);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri() -> Url {
+        Url::parse("file:///test.R").unwrap()
+    }
+
+    #[test]
+    fn invalidate_removes_an_indexed_file() {
+        let mut index = WorkspaceIndex::new();
+        index.insert(uri());
+        index.invalidate(&uri());
+        assert!(!index.contains(&uri()));
+    }
+}
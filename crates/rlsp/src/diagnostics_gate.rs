@@ -0,0 +1,178 @@
+//
+// diagnostics_gate.rs
+//
+// Monotonic version gate for diagnostics publication
+//
+
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+use tower_lsp::lsp_types::Url;
+
+/// The two independent sources of diagnostics that get merged before
+/// publishing: tree-sitter-derived diagnostics computed synchronously on
+/// every edit, and lintr diagnostics that arrive later from an async
+/// flycheck run. Each is gated separately, keyed by `(Url, DiagnosticsChannel)`,
+/// so a slow external result for an old version can't be blocked by (or
+/// silently clobber) a native publish that has since moved on to a newer one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticsChannel {
+    Native,
+    External,
+}
+
+/// Prevents re-publishing diagnostics for a document version that's already
+/// been published, or an older one that raced a newer edit, while still
+/// letting a same-version republish through when [`mark_force_republish`]
+/// says this file is a dependent whose cross-file context changed out from
+/// under it.
+///
+/// [`mark_force_republish`]: DiagnosticsGate::mark_force_republish
+#[derive(Debug, Default)]
+pub struct DiagnosticsGate {
+    last_published: RwLock<HashMap<(Url, DiagnosticsChannel), i32>>,
+    force_republish: RwLock<HashSet<Url>>,
+}
+
+impl DiagnosticsGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `uri` at `version` should actually be published on `channel`:
+    /// a newer (or first-seen) version on that channel always may; an equal
+    /// version may only if it was flagged via `mark_force_republish` since
+    /// the last publish; an older version never may.
+    pub fn can_publish(&self, uri: &Url, version: i32, channel: DiagnosticsChannel) -> bool {
+        let last = self
+            .last_published
+            .read()
+            .ok()
+            .and_then(|published| published.get(&(uri.clone(), channel)).copied());
+
+        match last {
+            Some(last) if version < last => false,
+            Some(last) if version == last => self
+                .force_republish
+                .read()
+                .map(|forced| forced.contains(uri))
+                .unwrap_or(false),
+            _ => true,
+        }
+    }
+
+    /// Record that `uri` was just published at `version` on `channel`,
+    /// clearing any pending force-republish flag for it.
+    pub fn record_publish(&self, uri: &Url, version: i32, channel: DiagnosticsChannel) {
+        if let Ok(mut published) = self.last_published.write() {
+            published.insert((uri.clone(), channel), version);
+        }
+        if let Ok(mut forced) = self.force_republish.write() {
+            forced.remove(uri);
+        }
+    }
+
+    /// Allow the next `can_publish` call for `uri` to succeed even at its
+    /// already-published version, because a dependency's export surface
+    /// changed and `uri` needs to be re-surfaced against it.
+    pub fn mark_force_republish(&self, uri: &Url) {
+        if let Ok(mut forced) = self.force_republish.write() {
+            forced.insert(uri.clone());
+        }
+    }
+
+    pub fn clear(&self, uri: &Url) {
+        if let Ok(mut published) = self.last_published.write() {
+            published.retain(|(published_uri, _), _| published_uri != uri);
+        }
+        if let Ok(mut forced) = self.force_republish.write() {
+            forced.remove(uri);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_uri() -> Url {
+        Url::parse("file:///test.R").unwrap()
+    }
+
+    #[test]
+    fn first_publish_of_any_version_is_allowed() {
+        let gate = DiagnosticsGate::new();
+        assert!(gate.can_publish(&test_uri(), 1, DiagnosticsChannel::Native));
+    }
+
+    #[test]
+    fn older_version_is_rejected() {
+        let gate = DiagnosticsGate::new();
+        let uri = test_uri();
+        gate.record_publish(&uri, 5, DiagnosticsChannel::Native);
+        assert!(!gate.can_publish(&uri, 4, DiagnosticsChannel::Native));
+    }
+
+    #[test]
+    fn newer_version_is_allowed() {
+        let gate = DiagnosticsGate::new();
+        let uri = test_uri();
+        gate.record_publish(&uri, 5, DiagnosticsChannel::Native);
+        assert!(gate.can_publish(&uri, 6, DiagnosticsChannel::Native));
+    }
+
+    #[test]
+    fn same_version_is_rejected_without_force_republish() {
+        let gate = DiagnosticsGate::new();
+        let uri = test_uri();
+        gate.record_publish(&uri, 5, DiagnosticsChannel::Native);
+        assert!(!gate.can_publish(&uri, 5, DiagnosticsChannel::Native));
+    }
+
+    #[test]
+    fn same_version_is_allowed_after_force_republish() {
+        let gate = DiagnosticsGate::new();
+        let uri = test_uri();
+        gate.record_publish(&uri, 5, DiagnosticsChannel::Native);
+        gate.mark_force_republish(&uri);
+        assert!(gate.can_publish(&uri, 5, DiagnosticsChannel::Native));
+    }
+
+    #[test]
+    fn force_republish_flag_is_consumed_by_the_next_publish() {
+        let gate = DiagnosticsGate::new();
+        let uri = test_uri();
+        gate.record_publish(&uri, 5, DiagnosticsChannel::Native);
+        gate.mark_force_republish(&uri);
+        gate.record_publish(&uri, 5, DiagnosticsChannel::Native);
+        assert!(!gate.can_publish(&uri, 5, DiagnosticsChannel::Native));
+    }
+
+    #[test]
+    fn clear_forgets_the_document_entirely() {
+        let gate = DiagnosticsGate::new();
+        let uri = test_uri();
+        gate.record_publish(&uri, 5, DiagnosticsChannel::Native);
+        gate.clear(&uri);
+        assert!(gate.can_publish(&uri, 5, DiagnosticsChannel::Native));
+    }
+
+    #[test]
+    fn native_publish_does_not_block_external_publish_of_the_same_version() {
+        let gate = DiagnosticsGate::new();
+        let uri = test_uri();
+        gate.record_publish(&uri, 5, DiagnosticsChannel::Native);
+        assert!(gate.can_publish(&uri, 5, DiagnosticsChannel::External));
+    }
+
+    #[test]
+    fn each_channel_tracks_its_own_last_published_version() {
+        let gate = DiagnosticsGate::new();
+        let uri = test_uri();
+        gate.record_publish(&uri, 5, DiagnosticsChannel::Native);
+        gate.record_publish(&uri, 2, DiagnosticsChannel::External);
+        assert!(gate.can_publish(&uri, 6, DiagnosticsChannel::Native));
+        assert!(!gate.can_publish(&uri, 1, DiagnosticsChannel::External));
+        assert!(gate.can_publish(&uri, 3, DiagnosticsChannel::External));
+    }
+}
@@ -0,0 +1,251 @@
+//
+// flycheck.rs
+//
+// Background external-linter subsystem (lintr), run out-of-process and
+// merged with the native tree-sitter diagnostics before publishing.
+//
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range, Url};
+
+/// Diagnostics for every open document, split by where they came from.
+///
+/// `native` is filled by `handlers::diagnostics` (the in-process
+/// tree-sitter analysis); `external` is filled by the lintr background
+/// task below. `publish_diagnostics` publishes their concatenation, so a
+/// stale or slow lintr run never has to block the fast native path.
+#[derive(Debug, Default)]
+pub struct DiagnosticCollection {
+    native: RwLock<HashMap<Url, Vec<Diagnostic>>>,
+    external: RwLock<HashMap<Url, Vec<Diagnostic>>>,
+}
+
+impl DiagnosticCollection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_native(&self, uri: Url, diagnostics: Vec<Diagnostic>) {
+        if let Ok(mut native) = self.native.write() {
+            native.insert(uri, diagnostics);
+        }
+    }
+
+    /// Atomically replace the external (lintr) diagnostics for `uri`.
+    pub fn set_external(&self, uri: Url, diagnostics: Vec<Diagnostic>) {
+        if let Ok(mut external) = self.external.write() {
+            external.insert(uri, diagnostics);
+        }
+    }
+
+    pub fn clear(&self, uri: &Url) {
+        if let Ok(mut native) = self.native.write() {
+            native.remove(uri);
+        }
+        if let Ok(mut external) = self.external.write() {
+            external.remove(uri);
+        }
+    }
+
+    /// The diagnostics that should actually be published for `uri`: native
+    /// findings first, then whatever lintr last reported.
+    pub fn combined(&self, uri: &Url) -> Vec<Diagnostic> {
+        let mut combined = self
+            .native
+            .read()
+            .ok()
+            .and_then(|native| native.get(uri).cloned())
+            .unwrap_or_default();
+        if let Some(external) = self.external.read().ok().and_then(|e| e.get(uri).cloned()) {
+            combined.extend(external);
+        }
+        combined
+    }
+}
+
+/// How long to wait after the most recent request (for any file) before
+/// actually spawning lintr, so a burst of keystrokes only triggers one run.
+const DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// One file's worth of work for the background linter loop.
+struct LintRequest {
+    uri: Url,
+    path: PathBuf,
+    version: Option<i32>,
+}
+
+/// Handle to the background lintr task. Cloning is cheap; every clone
+/// shares the same debounced request queue.
+#[derive(Clone)]
+pub struct FlycheckHandle {
+    sender: tokio::sync::mpsc::UnboundedSender<LintRequest>,
+}
+
+impl FlycheckHandle {
+    /// Spawn the background task that debounces lint requests, runs
+    /// `lintr::lint()` out-of-process, and calls `on_result` with the
+    /// parsed diagnostics once a run completes.
+    pub fn spawn<F>(on_result: F) -> Self
+    where
+        F: Fn(Url, Option<i32>, Vec<Diagnostic>) + Send + Sync + 'static,
+    {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel::<LintRequest>();
+
+        tokio::spawn(async move {
+            // Coalesce to the most recent request per URI within DEBOUNCE,
+            // so a rapid series of edits to the same file only lints once.
+            let mut pending: HashMap<Url, LintRequest> = HashMap::new();
+
+            loop {
+                let next = tokio::time::timeout(DEBOUNCE, receiver.recv()).await;
+                match next {
+                    Ok(Some(request)) => {
+                        pending.insert(request.uri.clone(), request);
+                        continue;
+                    }
+                    Ok(None) => break, // all senders dropped
+                    Err(_elapsed) => {}
+                }
+
+                for (_, request) in pending.drain() {
+                    match run_lintr(&request.path).await {
+                        Ok(diagnostics) => on_result(request.uri, request.version, diagnostics),
+                        Err(err) => {
+                            log::warn!("lintr run failed for {}: {}", request.path.display(), err);
+                        }
+                    }
+                }
+            }
+        });
+
+        Self { sender }
+    }
+
+    /// Queue a lint run for `uri`. Debounced: a rapid series of calls for
+    /// the same file collapses into a single lintr invocation.
+    pub fn request(&self, uri: Url, path: PathBuf, version: Option<i32>) {
+        let _ = self.sender.send(LintRequest { uri, path, version });
+    }
+}
+
+/// One entry of lintr's `lint()` output, as emitted by the inline
+/// `jsonlite` script that [`run_lintr`] passes to `Rscript -e`.
+#[derive(Debug, Deserialize)]
+struct LintrDiagnostic {
+    line_number: u32,
+    column_number: u32,
+    message: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+/// Run `lintr::lint()` on `path` out-of-process and parse its JSON output
+/// into LSP diagnostics, clamping ranges to a single-character span (lintr
+/// doesn't report an end position).
+async fn run_lintr(path: &std::path::Path) -> anyhow::Result<Vec<Diagnostic>> {
+    let script = format!(
+        "lints <- lintr::lint({path:?}); \
+         cat(jsonlite::toJSON(lapply(lints, function(l) list(\
+             line_number = l$line_number, \
+             column_number = l$column_number, \
+             message = l$message, \
+             type = l$type \
+         )), auto_unbox = TRUE))",
+        path = path.display()
+    );
+
+    let output = tokio::process::Command::new("Rscript")
+        .arg("--vanilla")
+        .arg("-e")
+        .arg(&script)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Rscript exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let lints: Vec<LintrDiagnostic> = serde_json::from_slice(&output.stdout)?;
+    Ok(lints.into_iter().map(lintr_diagnostic_to_lsp).collect())
+}
+
+fn lintr_diagnostic_to_lsp(lint: LintrDiagnostic) -> Diagnostic {
+    // lintr's line/column are 1-based; clamp the column to a minimum of 0
+    // since a malformed lint entry shouldn't be able to underflow here.
+    let line = lint.line_number.saturating_sub(1);
+    let column = lint.column_number.saturating_sub(1);
+    let start = Position::new(line, column);
+    let end = Position::new(line, column + 1);
+
+    let severity = match lint.kind.as_str() {
+        "error" => DiagnosticSeverity::ERROR,
+        "warning" => DiagnosticSeverity::WARNING,
+        _ => DiagnosticSeverity::INFORMATION,
+    };
+
+    Diagnostic {
+        range: Range::new(start, end),
+        severity: Some(severity),
+        source: Some("lintr".to_string()),
+        message: lint.message,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(kind: &str) -> LintrDiagnostic {
+        LintrDiagnostic {
+            line_number: 3,
+            column_number: 5,
+            message: "line is too long".to_string(),
+            kind: kind.to_string(),
+        }
+    }
+
+    #[test]
+    fn converts_1_based_line_and_column_to_0_based() {
+        let diagnostic = lintr_diagnostic_to_lsp(lint("warning"));
+        assert_eq!(diagnostic.range.start, Position::new(2, 4));
+        assert_eq!(diagnostic.range.end, Position::new(2, 5));
+    }
+
+    #[test]
+    fn clamps_a_leading_position_instead_of_underflowing() {
+        let diagnostic = lintr_diagnostic_to_lsp(LintrDiagnostic {
+            line_number: 0,
+            column_number: 0,
+            ..lint("warning")
+        });
+        assert_eq!(diagnostic.range.start, Position::new(0, 0));
+    }
+
+    #[test]
+    fn maps_error_kind_to_error_severity() {
+        let diagnostic = lintr_diagnostic_to_lsp(lint("error"));
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+    }
+
+    #[test]
+    fn maps_warning_kind_to_warning_severity() {
+        let diagnostic = lintr_diagnostic_to_lsp(lint("warning"));
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+    }
+
+    #[test]
+    fn maps_unrecognized_kind_to_information_severity() {
+        let diagnostic = lintr_diagnostic_to_lsp(lint("style"));
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::INFORMATION));
+    }
+}
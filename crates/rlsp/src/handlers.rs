@@ -0,0 +1,106 @@
+//
+// handlers.rs
+//
+// LSP request handlers. Read-only requests run against a `WorldSnapshot`
+// (see `Backend::with_snapshot`); requests that run directly under
+// `WorldState`'s lock (diagnostics, code actions) take `&WorldState`.
+//
+
+use tower_lsp::lsp_types::{
+    CompletionResponse, Diagnostic, DocumentSymbolResponse, FoldingRange, GotoDefinitionResponse,
+    Hover, Location, Position, SelectionRange, SignatureHelp, TextEdit, Url,
+};
+
+use crate::code_action::Fix;
+use crate::state::{WorldSnapshot, WorldState};
+
+// The handlers below don't yet have a tree-sitter-backed analysis engine to
+// query in this module - they return the "nothing known about this
+// document" answer rather than fabricate results. Each checks that the
+// document is actually open so a request for an unknown URI still returns
+// `None`/empty rather than silently succeeding.
+
+pub fn folding_range(snapshot: &WorldSnapshot, uri: &Url) -> Option<Vec<FoldingRange>> {
+    snapshot.documents.get(uri)?;
+    None
+}
+
+pub fn selection_range(
+    snapshot: &WorldSnapshot,
+    uri: &Url,
+    _positions: Vec<Position>,
+) -> Option<Vec<SelectionRange>> {
+    snapshot.documents.get(uri)?;
+    None
+}
+
+pub fn document_symbol(snapshot: &WorldSnapshot, uri: &Url) -> Option<DocumentSymbolResponse> {
+    snapshot.documents.get(uri)?;
+    None
+}
+
+pub fn completion(
+    snapshot: &WorldSnapshot,
+    uri: &Url,
+    _position: Position,
+) -> Option<CompletionResponse> {
+    snapshot.documents.get(uri)?;
+    None
+}
+
+pub fn hover(snapshot: &WorldSnapshot, uri: &Url, _position: Position) -> Option<Hover> {
+    snapshot.documents.get(uri)?;
+    None
+}
+
+pub fn signature_help(
+    snapshot: &WorldSnapshot,
+    uri: &Url,
+    _position: Position,
+) -> Option<SignatureHelp> {
+    snapshot.documents.get(uri)?;
+    None
+}
+
+pub fn goto_definition(
+    snapshot: &WorldSnapshot,
+    uri: &Url,
+    _position: Position,
+) -> Option<GotoDefinitionResponse> {
+    snapshot.documents.get(uri)?;
+    None
+}
+
+pub fn references(
+    snapshot: &WorldSnapshot,
+    uri: &Url,
+    _position: Position,
+) -> Option<Vec<Location>> {
+    snapshot.documents.get(uri)?;
+    None
+}
+
+pub fn on_type_formatting(
+    snapshot: &WorldSnapshot,
+    uri: &Url,
+    _position: Position,
+) -> Option<Vec<TextEdit>> {
+    snapshot.documents.get(uri)?;
+    None
+}
+
+/// Native (tree-sitter) diagnostics for `uri`, merged with lintr's in
+/// `Backend::publish_diagnostics`. No native analysis is wired into this
+/// module yet, so this always reports a clean document.
+pub fn diagnostics(_state: &WorldState, _uri: &Url) -> Vec<Diagnostic> {
+    Vec::new()
+}
+
+/// Quick-fixes to attach to `uri`'s native diagnostics, surfaced via
+/// `textDocument/codeAction`. This is scaffolding only: the `code_action`
+/// plumbing (capability advertisement, handler, `FixCollection` storage) is
+/// wired up end to end, but no concrete fix is implemented here yet, so this
+/// always returns an empty list regardless of what [`diagnostics`] reports.
+pub fn code_action_fixes(_state: &WorldState, _uri: &Url) -> Vec<Fix> {
+    Vec::new()
+}
@@ -0,0 +1,332 @@
+//
+// state.rs
+//
+// In-memory workspace state: open documents and the cross-file awareness
+// indexes/caches derived from them.
+//
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{Range, TextDocumentContentChangeEvent, Url};
+
+use raven::cross_file::cache::{MetadataCache, RedbMetadataStore};
+
+use crate::cross_file::dependency::DependencyGraph;
+use crate::cross_file::file_cache::FileCache;
+use crate::cross_file::path_auditor::PathAuditor;
+use crate::cross_file::path_resolve::{
+    resolve_symlink_aware, AbsPathBuf, PathContext, RelPath, RelPathBuf,
+};
+use crate::cross_file::revalidation::RevalidationQueue;
+use crate::cross_file::workspace_index::WorkspaceIndex;
+use crate::cross_file::{
+    export_surface_hash, parse_directives, CrossFileConfig, CrossFileMetadata, MetadataStoreBackend,
+};
+use crate::diagnostics_gate::DiagnosticsGate;
+
+/// Matches [`MetadataCache`]'s own default; used as the LRU capacity in
+/// front of a persistent store, same as the purely in-memory cache.
+const METADATA_CACHE_CAPACITY: usize = 1000;
+
+/// A single open document: its current text and the version the client
+/// last reported for it (`None` until the first `didOpen`/`didChange`).
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub text: String,
+    pub version: Option<i32>,
+}
+
+/// Tracks which open documents were interacted with most recently, so a
+/// revalidation cascade can prioritize the files a user is actually looking
+/// at over ones only pulled in transitively by a dependency edit.
+#[derive(Debug, Default)]
+pub struct ActivityTracker {
+    recent: VecDeque<Url>,
+}
+
+impl ActivityTracker {
+    /// How many recently-touched documents to remember; older entries just
+    /// fall back to the lowest priority tier instead of being tracked.
+    const CAPACITY: usize = 20;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `uri` as just opened or edited, moving it to the front of the
+    /// recency list.
+    pub fn record_recent(&mut self, uri: Url) {
+        self.recent.retain(|u| u != &uri);
+        self.recent.push_front(uri);
+        self.recent.truncate(Self::CAPACITY);
+    }
+
+    pub fn remove(&mut self, uri: &Url) {
+        self.recent.retain(|u| u != uri);
+    }
+
+    /// Lower is higher priority. A file near the front of the recency list
+    /// sorts before one that's further back or untracked entirely.
+    pub fn priority_score(&self, uri: &Url) -> usize {
+        self.recent
+            .iter()
+            .position(|u| u == uri)
+            .unwrap_or(self.recent.len())
+    }
+}
+
+/// A cheap, point-in-time, shareable view of the world for read-only
+/// handlers to run against off the async lock.
+///
+/// `with_snapshot` captures this while holding `WorldState`'s read lock and
+/// releases the lock immediately afterward; the handler then runs against
+/// its own `Arc`-backed copy on a blocking thread, so it can't stall
+/// `did_change` or be invalidated by a concurrent edit.
+#[derive(Debug, Clone)]
+pub struct WorldSnapshot {
+    pub documents: Arc<HashMap<Url, Arc<Document>>>,
+    pub cross_file_meta: Arc<HashMap<Url, Arc<CrossFileMetadata>>>,
+}
+
+/// All server-owned state for the workspace: open documents plus the
+/// cross-file awareness indexes/caches derived from them.
+#[derive(Debug)]
+pub struct WorldState {
+    pub workspace_folders: Vec<Url>,
+    pub documents: HashMap<Url, Arc<Document>>,
+    pub cross_file_meta: HashMap<Url, Arc<CrossFileMetadata>>,
+    pub cross_file_activity: ActivityTracker,
+    pub cross_file_graph: DependencyGraph,
+    pub cross_file_config: CrossFileConfig,
+    pub diagnostics_gate: DiagnosticsGate,
+    pub cross_file_revalidation: RevalidationQueue,
+    pub cross_file_file_cache: FileCache,
+    pub cross_file_workspace_index: WorkspaceIndex,
+    pub cross_file_cache: MetadataCache,
+    /// Enforces that directive paths resolve to somewhere under a workspace
+    /// root, shared across every `reparse()` call.
+    pub cross_file_path_auditor: PathAuditor,
+    library_paths: Vec<PathBuf>,
+}
+
+impl WorldState {
+    pub fn new(library_paths: Vec<PathBuf>) -> Self {
+        let cross_file_config = CrossFileConfig::default();
+        let cross_file_cache = build_metadata_cache(&cross_file_config);
+
+        Self {
+            workspace_folders: Vec::new(),
+            documents: HashMap::new(),
+            cross_file_meta: HashMap::new(),
+            cross_file_activity: ActivityTracker::new(),
+            cross_file_graph: DependencyGraph::new(),
+            cross_file_config,
+            diagnostics_gate: DiagnosticsGate::new(),
+            cross_file_revalidation: RevalidationQueue::new(),
+            cross_file_file_cache: FileCache::new(),
+            cross_file_workspace_index: WorkspaceIndex::new(),
+            cross_file_cache,
+            cross_file_path_auditor: PathAuditor::new(),
+            library_paths,
+        }
+    }
+
+    /// R library paths discovered at startup, for handlers that need to
+    /// resolve a package namespace (e.g. completion, hover over `pkg::fn`).
+    pub fn library_paths(&self) -> &[PathBuf] {
+        &self.library_paths
+    }
+
+    /// A cheap, `Arc`-backed copy of the document and cross-file-metadata
+    /// maps, safe to hand to a read-only handler after this state's lock is
+    /// released. Cloning the maps still copies their keys, but each value is
+    /// an `Arc` whose clone is a pointer bump, so a snapshot no longer
+    /// deep-copies every open document's text on every read-only request.
+    pub fn snapshot(&self) -> WorldSnapshot {
+        WorldSnapshot {
+            documents: Arc::new(self.documents.clone()),
+            cross_file_meta: Arc::new(self.cross_file_meta.clone()),
+        }
+    }
+
+    pub fn index_workspace(&mut self) {
+        // Full workspace indexing walks every file under
+        // `workspace_folders` and populates `cross_file_workspace_index`;
+        // that walk isn't implemented yet, so there's nothing to do until
+        // a file is actually opened.
+    }
+
+    pub fn open_document(&mut self, uri: Url, text: &str, version: Option<i32>) {
+        self.documents.insert(
+            uri.clone(),
+            Arc::new(Document {
+                text: text.to_string(),
+                version,
+            }),
+        );
+
+        // `cross_file_cache` persists across server restarts (when backed by
+        // a `Redb` store), so a just-opened document may already have
+        // metadata from a previous session; reusing it skips a full re-parse
+        // on every cold-start open instead of always falling through to
+        // `reparse()`.
+        match self.cross_file_cache.get(&uri) {
+            Some(meta) => self.apply_metadata(&uri, meta),
+            None => self.reparse(&uri),
+        }
+    }
+
+    pub fn close_document(&mut self, uri: &Url) {
+        self.documents.remove(uri);
+    }
+
+    pub fn apply_change(&mut self, uri: &Url, change: TextDocumentContentChangeEvent) {
+        if let Some(doc) = self.documents.get_mut(uri) {
+            // Every change is applied as a full-document replacement for
+            // now; incremental `range`-based edits aren't threaded through
+            // yet even though the server advertises incremental sync.
+            //
+            // `make_mut` clones the `Document` only if a snapshot still
+            // holds a reference to this `Arc`; otherwise it mutates in
+            // place, same as the old bare `HashMap<Url, Document>` did.
+            Arc::make_mut(doc).text = change.text;
+        }
+        self.reparse(uri);
+    }
+
+    /// Re-derive `uri`'s cross-file metadata from its current (open-document)
+    /// text, populating `export_surface_hash` so `did_change` can tell a
+    /// cosmetic edit from one a dependent file can actually observe, and
+    /// update the dependency graph to match its (possibly changed)
+    /// directives.
+    fn reparse(&mut self, uri: &Url) {
+        let Some(doc) = self.documents.get(uri) else {
+            return;
+        };
+        let text = doc.text.clone();
+        self.reparse_text(uri, &text);
+    }
+
+    /// Re-derive `uri`'s cross-file metadata by reading its current contents
+    /// from disk, for a file the client has changed externally (a watched
+    /// file that isn't an open document, so there's no in-memory text to
+    /// reparse from). No-op if `uri` isn't a `file://` URI or the read
+    /// fails (e.g. the file was removed out from under this call).
+    pub fn reparse_from_disk(&mut self, uri: &Url) {
+        let Ok(path) = uri.to_file_path() else {
+            return;
+        };
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return;
+        };
+        self.reparse_text(uri, &text);
+    }
+
+    fn reparse_text(&mut self, uri: &Url, text: &str) {
+        let mut meta = parse_directives(text);
+        meta.export_surface_hash = export_surface_hash(&meta);
+
+        self.cross_file_cache.insert(uri.clone(), meta.clone());
+        self.apply_metadata(uri, meta);
+    }
+
+    /// Install already-derived `meta` for `uri`: update the dependency graph
+    /// from its directives and record it in `cross_file_meta`. Shared by
+    /// `reparse()` (metadata just parsed from text) and `open_document()`
+    /// (metadata reused from `cross_file_cache`), so a cache hit gets the
+    /// same graph wiring a fresh parse would.
+    fn apply_metadata(&mut self, uri: &Url, meta: CrossFileMetadata) {
+        let ctx = self.directive_path_context(uri, meta.working_directory.clone());
+
+        let targets = meta
+            .sourced_by
+            .iter()
+            .map(|directive| &directive.path)
+            .chain(meta.sources.iter().map(|directive| &directive.path))
+            .filter_map(|path| self.resolve_directive_target(path.as_rel_path(), ctx.as_ref()));
+        self.cross_file_graph.set_dependencies(uri, targets);
+
+        self.cross_file_meta.insert(uri.clone(), Arc::new(meta));
+    }
+
+    /// Build the [`PathContext`] `uri`'s directives should resolve against:
+    /// its own absolute path, the workspace folder that contains it (or
+    /// else the first workspace folder; `None` only when there are no
+    /// workspace folders), and the `@lsp-working-directory` this reparse
+    /// just parsed out of it.
+    ///
+    /// Returns `None` if `uri` isn't a `file://` URI; directives in
+    /// non-file documents have nothing meaningful to resolve against.
+    fn directive_path_context(
+        &self,
+        uri: &Url,
+        working_directory: Option<RelPathBuf>,
+    ) -> Option<PathContext> {
+        let file_path = AbsPathBuf::try_new(uri.to_file_path().ok()?).ok()?;
+
+        let workspace_root = self
+            .workspace_folders
+            .iter()
+            .filter_map(|folder| folder.to_file_path().ok())
+            .filter_map(|path| AbsPathBuf::try_new(path).ok())
+            .find(|root| {
+                file_path
+                    .as_path()
+                    .as_path()
+                    .starts_with(root.as_path().as_path())
+            })
+            .or_else(|| {
+                self.workspace_folders.first().and_then(|folder| {
+                    folder
+                        .to_file_path()
+                        .ok()
+                        .and_then(|path| AbsPathBuf::try_new(path).ok())
+                })
+            });
+
+        Some(PathContext {
+            file_path,
+            working_directory,
+            inherited_working_directory: None,
+            workspace_root,
+            symlink_mode: Default::default(),
+        })
+    }
+
+    /// Resolve a directive's `path` to the `Url` of the file it refers to,
+    /// auditing it against `self.cross_file_path_auditor` so a directive
+    /// can't walk a dependency edge outside the workspace root.
+    fn resolve_directive_target(&self, path: &RelPath, ctx: Option<&PathContext>) -> Option<Url> {
+        let ctx = ctx?;
+        match resolve_symlink_aware(path, ctx, &self.cross_file_path_auditor, Range::default())? {
+            Ok(resolved) => Url::from_file_path(resolved.effective_path().as_path()).ok(),
+            Err(diagnostic) => {
+                log::trace!("Rejected directive path {}: {:?}", path.as_str(), diagnostic);
+                None
+            }
+        }
+    }
+}
+
+/// Build the `MetadataCache` backing `cross_file_cache` from the configured
+/// [`MetadataStoreBackend`], falling back to a purely in-memory cache if the
+/// persistent backend fails to open (e.g. an unwritable path) rather than
+/// failing server startup over it.
+fn build_metadata_cache(config: &CrossFileConfig) -> MetadataCache {
+    match &config.metadata_store {
+        MetadataStoreBackend::Memory => MetadataCache::new(),
+        MetadataStoreBackend::Redb { path } => match RedbMetadataStore::open(path) {
+            Ok(store) => MetadataCache::with_store(METADATA_CACHE_CAPACITY, Arc::new(store)),
+            Err(err) => {
+                log::warn!(
+                    "Failed to open redb metadata store at {}: {}; falling back to an in-memory cache",
+                    path.display(),
+                    err
+                );
+                MetadataCache::new()
+            }
+        },
+    }
+}